@@ -0,0 +1,100 @@
+use std::{
+    net::TcpListener,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui::Context;
+
+use crate::{connect, WordgamesClient};
+
+pub const DEFAULT_MESSAGE_COUNT: usize = 10_000;
+
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn nanos_since_epoch() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos())
+}
+
+fn serve_loopback(listener: TcpListener, message_count: usize) -> Result<(), String> {
+    let (stream, _) = listener.accept().map_err(|err| err.to_string())?;
+    let mut socket = tungstenite::accept(stream).map_err(|err| err.to_string())?;
+
+    for _ in 0..message_count {
+        // the chat content carries the send time so the client can measure latency
+        let message = format!(
+            r#"{{"type":"ChatMessage","content":"{}"}}"#,
+            nanos_since_epoch()
+        );
+        socket
+            .write_message(tungstenite::Message::Text(message))
+            .map_err(|err| err.to_string())?;
+    }
+
+    // keep the connection open until the client is done reading
+    while socket.read_message().is_ok() {}
+    Ok(())
+}
+
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+/// Measures how fast messages travel from a loopback server through the reader thread
+/// and channel into `ws_result_received`, printing the results to stdout
+pub fn run(message_count: usize) -> Result<(), String> {
+    if message_count == 0 {
+        return Err("message count must be at least 1".to_owned());
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|err| err.to_string())?;
+    let addr = listener.local_addr().map_err(|err| err.to_string())?;
+    thread::spawn(move || {
+        if let Err(err) = serve_loopback(listener, message_count) {
+            eprintln!("Loopback server failed: {err}");
+        }
+    });
+
+    let (_sender, receiver) = connect(&format!("ws://{addr}"), Context::default())?;
+    let mut client = WordgamesClient::default();
+    let mut latencies = Vec::with_capacity(message_count);
+
+    let started = Instant::now();
+    while latencies.len() < message_count {
+        let result = receiver
+            .recv_timeout(RECEIVE_TIMEOUT)
+            .map_err(|err| format!("after {} messages: {err}", latencies.len()))?;
+        client.ws_result_received(result);
+
+        let sent_at: u128 = client
+            .messages
+            .last()
+            .and_then(|entry| entry.text.parse().ok())
+            .ok_or("received an unexpected message")?;
+        let latency = nanos_since_epoch().saturating_sub(sent_at);
+        latencies.push(Duration::from_nanos(latency as u64));
+    }
+    let elapsed = started.elapsed();
+
+    latencies.sort();
+    println!("Messages:   {message_count}");
+    println!("Elapsed:    {:.3} s", elapsed.as_secs_f64());
+    println!(
+        "Throughput: {:.1} messages/s",
+        message_count as f64 / elapsed.as_secs_f64()
+    );
+    for percent in [50, 90, 99] {
+        println!(
+            "p{percent}:        {:.3} ms",
+            percentile(&latencies, percent).as_secs_f64() * 1000.0
+        );
+    }
+    println!(
+        "Max:        {:.3} ms",
+        percentile(&latencies, 100).as_secs_f64() * 1000.0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use eframe::egui::{Context, Key, KeyboardShortcut, Modifiers};
+
+pub struct Hotkey {
+    pub shortcut: KeyboardShortcut,
+    pub description: &'static str,
+}
+
+impl Hotkey {
+    const fn new(modifiers: Modifiers, key: Key, description: &'static str) -> Self {
+        Self {
+            shortcut: KeyboardShortcut::new(modifiers, key),
+            description,
+        }
+    }
+
+    /// Checks for the shortcut and keeps widgets from also reacting to it
+    pub fn consume(&self, ctx: &Context) -> bool {
+        ctx.input_mut(|i| i.consume_shortcut(&self.shortcut))
+    }
+}
+
+pub const SEND_MESSAGE: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::Enter,
+    "Send the message (when Send with Enter is on)",
+);
+pub const COMPLETE_NAME: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::Tab,
+    "Complete the highlighted player name",
+);
+pub const NEXT_NAME: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::ArrowDown,
+    "Highlight the next player name suggestion",
+);
+pub const PREVIOUS_NAME: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::ArrowUp,
+    "Highlight the previous player name suggestion",
+);
+pub const RESEND_LAST: Hotkey = Hotkey::new(
+    Modifiers::COMMAND,
+    Key::R,
+    "Send the last sent message again",
+);
+pub const TOGGLE_FOCUS_MODE: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::F2,
+    "Enter or leave focus mode, which hides the chat",
+);
+pub const TOGGLE_SHORTCUTS: Hotkey = Hotkey::new(
+    Modifiers::NONE,
+    Key::F1,
+    "Show or hide this list of shortcuts",
+);
+pub const CLOSE_SHORTCUTS: Hotkey =
+    Hotkey::new(Modifiers::NONE, Key::Escape, "Close this list of shortcuts");
+
+/// Every key binding, as listed in the shortcuts window
+pub const HOTKEYS: &[Hotkey] = &[
+    SEND_MESSAGE,
+    COMPLETE_NAME,
+    NEXT_NAME,
+    PREVIOUS_NAME,
+    RESEND_LAST,
+    TOGGLE_FOCUS_MODE,
+    TOGGLE_SHORTCUTS,
+    CLOSE_SHORTCUTS,
+];
@@ -2,25 +2,88 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::{
-    sync::mpsc::{self, Receiver, Sender},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use eframe::{
     egui::{
-        style::Margin, CentralPanel, Context, Frame, Key, Response, RichText, ScrollArea, Stroke,
-        Style, TextStyle, TopBottomPanel, Window,
+        style::Margin,
+        text::{CCursor, CCursorRange},
+        text_edit::TextEditState,
+        Align, Align2, Area, Button, CentralPanel, Context, Frame, Grid, Id, LayerId, Layout,
+        Order, Response, RichText, ScrollArea, Stroke, TextEdit, TopBottomPanel, Ui, WidgetInfo,
+        WidgetType, Window,
     },
-    epaint::{Color32, FontId, Rounding, Shadow, Vec2},
+    epaint::{Color32, FontId, Vec2},
 };
-use serde::Deserialize;
+use practice::PracticeSession;
+use serde::{Deserialize, Serialize};
+use settings::{ChatLayout, Settings, SettingsAction, WordBoxAlignment};
+use theme::{create_app_style, Theme};
 use time::{format_description::well_known::Iso8601, OffsetDateTime};
-use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{
+    http::{uri::InvalidUri, Uri},
+    stream::MaybeTlsStream,
+    HandshakeError, WebSocket,
+};
+
+mod bench;
+mod hotkeys;
+mod practice;
+#[cfg(feature = "discord")]
+mod presence;
+mod settings;
+mod theme;
 
 const APP_NAME: &str = "Wordgames Client";
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Every user-visible change adds a line under the upcoming version,
+/// the What's new window shows the lines of the running version
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+const LAST_SEEN_VERSION_KEY: &str = "last_seen_version";
+const SERVER_URL_KEY: &str = "server_url";
+const SETTINGS_KEY: &str = "settings";
+const SENT_HISTORY_KEY: &str = "sent_history";
+/// Same limit as `tungstenite::connect`
+const MAX_REDIRECTS: u8 = 3;
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+/// How long a server gets to accept the connection and answer each step of the handshake
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECTION_LOG_CAPACITY: usize = 200;
+const SENT_HISTORY_CAPACITY: usize = 50;
+const REVEAL_LETTER_INTERVAL: Duration = Duration::from_millis(80);
+/// How long before an idle disconnect the warning shows up
+const IDLE_WARNING_TIME: Duration = Duration::from_secs(60);
+/// Typing this many letters of a name suggests it even without an `@`
+const NAME_SUGGESTION_MIN_LENGTH: usize = 3;
+const DISCONNECT_ALERT_DURATION: Duration = Duration::from_millis(1500);
+const DISCONNECT_ALERT_PULSE: Duration = Duration::from_millis(500);
+const MESSAGE_ARRIVAL_DURATION: Duration = Duration::from_millis(250);
+/// How far a new message slides up while it fades in
+const MESSAGE_SLIDE_DISTANCE: f32 = 8.0;
+/// Features announced to the server in the hello message
+const CLIENT_FEATURES: &[&str] = &["ready", "reactions"];
+
+fn main() -> Result<(), eframe::Error> {
+    // headless mode measuring message throughput: --bench [MESSAGE_COUNT]
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--bench") {
+        let message_count = args
+            .next()
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(bench::DEFAULT_MESSAGE_COUNT);
+        if let Err(err) = bench::run(message_count) {
+            eprintln!("Benchmark failed: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-fn main() {
     eframe::run_native(
         APP_NAME,
         eframe::NativeOptions {
@@ -30,59 +93,39 @@ fn main() {
             ..Default::default()
         },
         Box::new(|creation_ctx| {
-            let mut app_style = Style::default();
-
-            app_style.spacing.item_spacing = Vec2::new(12.0, 6.0);
-            app_style.spacing.button_padding = Vec2::new(6.0, 3.0);
-            app_style.spacing.window_margin = Margin::same(12.0);
-            app_style.spacing.menu_margin = Margin::same(12.0);
-
-            app_style
-                .text_styles
-                .insert(TextStyle::Small, FontId::proportional(11.0));
-            app_style
-                .text_styles
-                .insert(TextStyle::Body, FontId::proportional(14.0));
-            app_style
-                .text_styles
-                .insert(TextStyle::Monospace, FontId::monospace(14.0));
-            app_style
-                .text_styles
-                .insert(TextStyle::Button, FontId::proportional(14.0));
-            app_style
-                .text_styles
-                .insert(TextStyle::Heading, FontId::proportional(20.0));
-
-            app_style.visuals.window_stroke = Stroke::new(1.5, Color32::from_gray(60));
-            app_style.visuals.window_rounding = Rounding::same(6.0);
-            app_style.visuals.window_shadow = Shadow::small_dark();
-
-            app_style.visuals.widgets.noninteractive.rounding = Rounding::same(3.0);
-            app_style.visuals.widgets.inactive.rounding = Rounding::same(3.0);
-            app_style.visuals.widgets.hovered.rounding = Rounding::same(3.0);
-            app_style.visuals.widgets.active.rounding = Rounding::same(3.0);
-            app_style.visuals.widgets.open.rounding = Rounding::same(3.0);
-
-            app_style.visuals.widgets.noninteractive.bg_stroke =
-                Stroke::new(1.5, Color32::from_gray(60));
-            app_style.visuals.widgets.hovered.bg_stroke = Stroke::new(1.5, Color32::from_gray(150));
-            app_style.visuals.widgets.active.bg_stroke = Stroke::new(1.5, Color32::from_gray(255));
-            app_style.visuals.widgets.open.bg_stroke = Stroke::new(1.5, Color32::from_gray(60));
-
-            app_style.visuals.widgets.hovered.expansion = 0.75;
-            app_style.visuals.widgets.active.expansion = 0.75;
-
-            app_style.visuals.widgets.noninteractive.fg_stroke =
-                Stroke::new(1.5, Color32::from_gray(190));
-            app_style.visuals.widgets.inactive.fg_stroke =
-                Stroke::new(1.5, Color32::from_gray(220));
-            app_style.visuals.widgets.hovered.fg_stroke = Stroke::new(1.5, Color32::from_gray(250));
-            app_style.visuals.widgets.active.fg_stroke = Stroke::new(1.5, Color32::from_gray(255));
-            app_style.visuals.widgets.open.fg_stroke = Stroke::new(1.5, Color32::from_gray(220));
-
-            app_style.visuals.selection.stroke = Stroke::new(1.5, Color32::from_rgb(192, 222, 255));
-
-            creation_ctx.egui_ctx.set_style(app_style);
+            // everything read here is written back in `WordgamesClient::save`
+            let read = |key| {
+                creation_ctx
+                    .storage
+                    .and_then(|storage| storage.get_string(key))
+            };
+            let mut err_texts = Vec::new();
+            let mut migrations = Vec::new();
+            let settings = read(SETTINGS_KEY).map_or_else(Settings::default, |json| {
+                match Settings::load(&json) {
+                    Ok((settings, applied)) => {
+                        migrations = applied;
+                        settings
+                    }
+                    Err(err) => {
+                        err_texts.push(format!(
+                            "Couldn't read saved settings, using the defaults. {err}"
+                        ));
+                        Settings::default()
+                    }
+                }
+            });
+            let sent_history = read(SENT_HISTORY_KEY)
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            let theme = Theme::load(&settings.theme_path).unwrap_or_else(|err| {
+                err_texts.push(format!("Couldn't load theme, using the default one. {err}"));
+                Theme::default()
+            });
+            let pixels_per_point = creation_ctx.egui_ctx.pixels_per_point();
+            creation_ctx
+                .egui_ctx
+                .set_style(create_app_style(&theme, pixels_per_point));
 
             // TIMER HACK: Re-render UI every second
             let app_ctx = creation_ctx.egui_ctx.clone();
@@ -91,15 +134,110 @@ fn main() {
                 app_ctx.request_repaint();
             });
 
-            Box::<WordgamesClient>::default()
+            let last_seen_version = read(LAST_SEEN_VERSION_KEY);
+            let server_url = read(SERVER_URL_KEY).unwrap_or_default();
+
+            let mut client = WordgamesClient {
+                err_texts,
+                sent_history,
+                server_url,
+                settings,
+                show_whats_new: last_seen_version.as_deref() != Some(APP_VERSION),
+                styled_pixels_per_point: pixels_per_point,
+                theme,
+                ..Default::default()
+            };
+            // release builds on Windows have no console, so these go where players can see them
+            for migration in migrations {
+                client.log_connection_event(format!("Settings migration: {migration}"));
+            }
+            Box::new(client)
         }),
-    );
+    )
 }
 
-type ChannelWebsocket = (Sender<String>, Receiver<Result<String, String>>);
+enum WebsocketError {
+    /// Something went wrong, but the connection is still usable
+    Failed(String),
+    /// The connection is gone and its reader thread has stopped
+    Closed(String),
+}
+
+type ChannelWebsocket = (Sender<String>, Receiver<Result<String, WebsocketError>>);
+
+/// Host and port to open the TCP connection to.
+/// IPv6 literals keep their brackets in the URL (`ws://[::1]:3000`) but can't be resolved with them
+fn server_address(uri: &Uri) -> Result<(&str, u16), String> {
+    let default_port = match uri.scheme_str() {
+        Some("ws") => 80,
+        Some("wss") => 443,
+        _ => return Err("Server URL must start with ws:// or wss://".to_owned()),
+    };
+    let host = uri.host().ok_or("Server URL has no host")?;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+
+    Ok((host, uri.port_u16().unwrap_or(default_port)))
+}
+
+/// Tries each address the server's host resolves to, giving up on one that doesn't answer
+/// after `CONNECT_TIMEOUT` instead of waiting for the OS to
+fn open_tcp_stream(uri: &Uri) -> Result<TcpStream, String> {
+    let addrs = server_address(uri)?
+        .to_socket_addrs()
+        .map_err(|err| err.to_string())?;
+    let mut last_err = format!("{uri} didn't resolve to any address");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_nodelay(true).map_err(|err| err.to_string())?;
+                // the handshake blocks as well, `connect` makes the socket nonblocking after it
+                stream
+                    .set_read_timeout(Some(CONNECT_TIMEOUT))
+                    .map_err(|err| err.to_string())?;
+                stream
+                    .set_write_timeout(Some(CONNECT_TIMEOUT))
+                    .map_err(|err| err.to_string())?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = format!("{addr}: {err}"),
+        }
+    }
+    Err(last_err)
+}
+
+/// Opens the websocket, following redirects like `tungstenite::connect` does.
+/// That can't resolve bracketed IPv6 literals, so the TCP connection is opened here
+fn open_socket(mut uri: Uri) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, String> {
+    let mut redirects = 0;
+    loop {
+        let stream = open_tcp_stream(&uri)?;
+
+        match tungstenite::client_tls(uri, stream) {
+            Ok((socket, _)) => return Ok(socket),
+            Err(HandshakeError::Failure(tungstenite::Error::Http(response)))
+                if response.status().is_redirection() && redirects < MAX_REDIRECTS =>
+            {
+                let location = response
+                    .headers()
+                    .get("Location")
+                    .and_then(|location| location.to_str().ok())
+                    .ok_or("Server redirected without a Location")?;
+                uri = location
+                    .parse()
+                    .map_err(|err: InvalidUri| err.to_string())?;
+                redirects += 1;
+            }
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
 
 fn connect(url: &str, ctx: Context) -> Result<ChannelWebsocket, String> {
-    let (mut socket, _) = tungstenite::connect(url).map_err(|err| err.to_string())?;
+    let uri: Uri = url.parse().map_err(|err: InvalidUri| err.to_string())?;
+    let mut socket = open_socket(uri)?;
 
     match socket.get_ref() {
         MaybeTlsStream::Plain(stream) => stream
@@ -116,15 +254,60 @@ fn connect(url: &str, ctx: Context) -> Result<ChannelWebsocket, String> {
     let (from_main_thread_tx, from_main_thread_rx) = mpsc::channel();
 
     thread::spawn(move || loop {
-        if let Ok(message) = from_main_thread_rx.try_recv() {
-            if let Err(err) = socket.write_message(tungstenite::Message::Text(message)) {
-                to_main_thread_tx.send(Err(err.to_string())).unwrap();
-                ctx.request_repaint();
+        // one repaint covers everything handled in this cycle, however many messages that was
+        let mut repaint_needed = false;
+
+        loop {
+            match from_main_thread_rx.try_recv() {
+                Ok(message) => {
+                    if let Err(err) = socket.write_message(tungstenite::Message::Text(message)) {
+                        if to_main_thread_tx
+                            .send(Err(WebsocketError::Failed(err.to_string())))
+                            .is_err()
+                        {
+                            return;
+                        }
+                        repaint_needed = true;
+                    }
+                }
+                // the main thread dropped its end, so this connection was closed on purpose
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.write_pending();
+                    return;
+                }
+                Err(TryRecvError::Empty) => break,
             }
         }
 
-        if let Ok(message) = socket.read_message() {
-            to_main_thread_tx.send(Ok(message.to_string())).unwrap();
+        loop {
+            match socket.read_message() {
+                // tungstenite answers the close frame itself, the next read reports the closure
+                Ok(tungstenite::Message::Close(_)) => (),
+                Ok(message) => {
+                    if to_main_thread_tx.send(Ok(message.to_string())).is_err() {
+                        return;
+                    }
+                    repaint_needed = true;
+                }
+                Err(tungstenite::Error::Io(err)) if err.kind() == io::ErrorKind::WouldBlock => {
+                    break
+                }
+                Err(err) => {
+                    let reason = match err {
+                        tungstenite::Error::ConnectionClosed => {
+                            "Server closed the connection".to_owned()
+                        }
+                        err => err.to_string(),
+                    };
+                    let _ = to_main_thread_tx.send(Err(WebsocketError::Closed(reason)));
+                    ctx.request_repaint();
+                    return;
+                }
+            }
+        }
+
+        if repaint_needed {
             ctx.request_repaint();
         }
 
@@ -138,92 +321,1041 @@ fn connect(url: &str, ctx: Context) -> Result<ChannelWebsocket, String> {
 #[derive(Deserialize)]
 #[serde(tag = "type", content = "content")]
 enum ServerMessage {
-    ChatMessage(String),
+    /// Reply to the hello message, listing the features the server supports
+    Capabilities(Vec<String>),
+    ChatMessage(ChatContent),
     FinishedGame,
     FinishedRoundInfo {
-        word_answer: String,
+        word_answer: WordAnswer,
         to_next_round_time: String,
     },
     OngoingRoundInfo {
         word_to_guess: String,
         round_finish_time: String,
     },
+    /// An emote added to the chat message with ID `message_id`
+    Reaction {
+        message_id: String,
+        emote: String,
+    },
+}
+
+/// Everything the client sends, kept in one place so each frame has the shape servers expect
+#[derive(Serialize)]
+#[serde(tag = "type", content = "content")]
+enum ClientMessage {
+    Chat(String),
+    Hello {
+        features: Vec<String>,
+    },
+    Ready,
+    /// Sent exactly as given, for servers expecting a frame of their own
+    #[serde(skip)]
+    Raw(String),
+}
+
+impl ClientMessage {
+    fn to_frame(&self) -> String {
+        match self {
+            // servers read chat and guesses as plain text
+            ClientMessage::Chat(text) | ClientMessage::Raw(text) => text.clone(),
+            message => {
+                serde_json::to_string(message).expect("client messages always serialize to JSON")
+            }
+        }
+    }
+}
+
+/// What became of an outgoing message
+#[derive(PartialEq)]
+enum Delivery {
+    Sent,
+    /// Only written to the connection log, the server never saw it
+    Logged,
+    Failed,
+}
+
+/// The lines under `version`'s heading in the changelog
+fn changelog_section(changelog: &str, version: &str) -> String {
+    let heading = format!("## {version}");
+    changelog
+        .lines()
+        .skip_while(|line| line.trim_end() != heading)
+        .skip(1)
+        .take_while(|line| !line.starts_with("## "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+fn format_timestamp(time: OffsetDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Some game variants accept several words for one scramble
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WordAnswer {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl From<WordAnswer> for Vec<String> {
+    fn from(answer: WordAnswer) -> Self {
+        match answer {
+            WordAnswer::Single(word) => vec![word],
+            WordAnswer::Multiple(words) => words,
+        }
+    }
+}
+
+/// Servers supporting reactions send chat messages with an ID to attach them to
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChatContent {
+    Plain(String),
+    WithId { id: String, text: String },
+}
+
+fn is_same_word(guess: &str, answer: &str) -> bool {
+    let guess = guess.trim();
+    !guess.is_empty() && guess.to_lowercase() == answer.to_lowercase()
+}
+
+struct ChatEntry {
+    id: Option<String>,
+    text: String,
+    received_at: Instant,
+}
+
+impl ChatEntry {
+    fn new(text: String) -> Self {
+        Self {
+            id: None,
+            text,
+            received_at: Instant::now(),
+        }
+    }
+
+    /// From 0.0 when the message arrives to 1.0 once its arrival animation is done
+    fn arrival_progress(&self) -> f32 {
+        (self.received_at.elapsed().as_secs_f32() / MESSAGE_ARRIVAL_DURATION.as_secs_f32()).min(1.0)
+    }
+}
+
+fn chat_entry_ui(
+    ui: &mut Ui,
+    entry: &ChatEntry,
+    opacity: f32,
+    animate: bool,
+    text_size: Option<f32>,
+    reactions: Option<&BTreeMap<String, u32>>,
+) {
+    let progress = if animate {
+        entry.arrival_progress()
+    } else {
+        1.0
+    };
+    if progress < 1.0 {
+        ui.add_space((1.0 - progress) * MESSAGE_SLIDE_DISTANCE);
+    }
+
+    let color = ui
+        .visuals()
+        .text_color()
+        .linear_multiply(opacity * progress);
+    let text = RichText::new(&entry.text).color(color);
+    ui.label(match text_size {
+        Some(size) => text.size(size),
+        None => text,
+    });
+
+    if let Some(reactions) = reactions {
+        ui.horizontal_wrapped(|ui| {
+            ui.add_space(8.0);
+            for (emote, count) in reactions {
+                ui.label(
+                    RichText::new(format!("{emote} {count}"))
+                        .small()
+                        .color(color),
+                );
+            }
+        });
+    }
+}
+
+/// Guesses who sent a chat message from a `Name: message` prefix
+fn sender_name(message: &str) -> Option<&str> {
+    let (name, _) = message.split_once(": ")?;
+    let plausible = !name.is_empty()
+        && name.chars().count() <= 24
+        && !name.contains(char::is_whitespace)
+        // practice mode echoes guesses as "You: ..."
+        && name != "You";
+    plausible.then_some(name)
+}
+
+/// Removes ANSI escape sequences and other control characters, keeping newlines and tabs
+fn strip_control_sequences(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in @..=~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or the ESC \ string terminator
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // any other escape is a single character after ESC
+                _ => {}
+            },
+            '\n' | '\t' => stripped.push(c),
+            c if c.is_control() => {}
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+/// Shows the first `count` letters of `word`, leaving blanks for the rest
+fn reveal_letters(word: &str, count: usize) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(idx, letter)| if idx < count { letter } else { '_' })
+        .collect()
+}
+
+/// Spells out `word` one letter at a time, so screen readers don't try to pronounce a scramble
+fn spelled_out(word: &str) -> String {
+    word.chars().map(String::from).collect::<Vec<_>>().join(" ")
+}
+
+fn word_box_ui(ui: &mut Ui, word: &str, settings: &Settings, highlighted: bool) -> Response {
+    let word = &settings.word_box_casing.apply(word);
+    let letter_spacing = settings.word_box_letter_spacing;
+    let letter_text = |text: String| {
+        let text = RichText::new(text).code().size(32.0);
+        if highlighted {
+            text.color(Color32::from_rgb(120, 220, 120))
+        } else {
+            text
+        }
+    };
+
+    if letter_spacing <= 0.0 {
+        return ui.label(letter_text(word.to_owned()));
+    }
+
+    // draw letters one by one so the gap between them is not left to font kerning
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = letter_spacing;
+
+        // a horizontal row always starts at the left edge, so center it by hand
+        if settings.word_box_alignment == WordBoxAlignment::Center {
+            let letters_width: f32 = word
+                .chars()
+                .map(|letter| {
+                    ui.fonts(|fonts| {
+                        fonts
+                            .layout_no_wrap(
+                                letter.to_string(),
+                                FontId::monospace(32.0),
+                                Color32::TEMPORARY_COLOR,
+                            )
+                            .size()
+                            .x
+                    })
+                })
+                .sum();
+            let row_width =
+                letters_width + letter_spacing * word.chars().count().saturating_sub(1) as f32;
+            ui.add_space(((ui.available_width() - row_width) / 2.0).max(0.0));
+        }
+
+        for letter in word.chars() {
+            ui.label(letter_text(letter.to_string()));
+        }
+    })
+    .response
 }
 
 #[derive(Default)]
 struct WordgamesClient {
+    answers_revealed_at: Option<Instant>,
+    connected_url: String,
+    connection_log: VecDeque<(OffsetDateTime, String)>,
+    connection_lost_reason: Option<String>,
+    /// Log outgoing frames to the connection log instead of sending them
+    dry_run: bool,
+    /// When the last connection loss alert started flashing
+    disconnect_alert_at: Option<Instant>,
     err_texts: Vec<String>,
-    messages: Vec<String>,
+    focus_mode: bool,
+    gave_up_reconnecting: bool,
+    is_ready: bool,
+    last_interaction_at: Option<Instant>,
+    latest_next_round_time: Option<OffsetDateTime>,
+    latest_round_finish_time: Option<OffsetDateTime>,
+    messages: Vec<ChatEntry>,
+    /// How many messages there were when focus mode was entered
+    messages_before_focus: Option<usize>,
+    name_suggestion_idx: usize,
     message_to_send: String,
+    /// URL an automatic reconnect is trying, its connection arrives from a worker thread
+    pending_reconnect: Option<(String, Receiver<Result<ChannelWebsocket, String>>)>,
+    practice: Option<PracticeSession>,
+    #[cfg(feature = "discord")]
+    presence: Option<presence::DiscordPresence>,
+    /// URL the player connected to, `connected_url` differs from it after failing over
+    primary_url: String,
+    /// Whether the player was ready when the connection dropped, to replay it after reconnecting
+    ready_before_drop: bool,
+    /// Emote counts for each chat message ID
+    reactions: HashMap<String, BTreeMap<String, u32>>,
+    reconnect_at: Option<Instant>,
+    reconnect_attempts: u32,
+    revealed_answers: Vec<String>,
+    round_guesses: Vec<String>,
+    /// How late the current round arrived compared to the start the server announced
+    round_start_delay: Option<time::Duration>,
+    /// Rounds started since connecting, shown in the Discord presence
+    #[cfg(feature = "discord")]
+    rounds_played: u32,
+    /// Player names picked up from chat, for completing them in the message field
+    seen_names: BTreeSet<String>,
+    sent_history: Vec<String>,
+    /// `None` until the server replies to the hello message, in which case every control is shown
+    server_capabilities: Option<Vec<String>>,
     server_url: String,
+    settings: Settings,
+    show_connection_log: bool,
+    show_settings: bool,
+    show_shortcuts: bool,
+    show_switch_confirmation: bool,
+    show_whats_new: bool,
+    /// Results moving to the next round at or before this are from rounds before the one on screen
+    stale_result_time: Option<OffsetDateTime>,
     status_text: String,
+    /// Scale the current style was built for
+    styled_pixels_per_point: f32,
+    theme: Theme,
     timer_finish_time: Option<OffsetDateTime>,
+    /// Messages that arrived during the last focus mode, counted when it was left
+    unread_from_focus: usize,
     websocket: Option<ChannelWebsocket>,
     word_box: String,
 }
 
 impl WordgamesClient {
-    fn ws_result_received(&mut self, result: Result<String, String>) {
+    fn ws_result_received(&mut self, result: Result<String, WebsocketError>) {
         match result {
-            Ok(message) => match serde_json::from_str::<ServerMessage>(&message).unwrap() {
-                ServerMessage::ChatMessage(message) => {
-                    self.messages.push(message);
+            // a server newer than the client may send frames it doesn't know yet
+            Ok(message) => match serde_json::from_str(&message) {
+                Ok(message) => self.server_message_received(message),
+                Err(err) => self.log_connection_event(format!("Skipped frame ({err}): {message}")),
+            },
+            Err(WebsocketError::Failed(err)) => {
+                self.log_connection_event(format!("Error: {err}"));
+                self.err_texts.push(err);
+            }
+            Err(WebsocketError::Closed(reason)) => self.connection_lost(reason),
+        }
+    }
+
+    fn server_message_received(&mut self, message: ServerMessage) {
+        match message {
+            ServerMessage::Capabilities(capabilities) => {
+                self.log_connection_event(format!("Server supports: {}", capabilities.join(", ")));
+                self.server_capabilities = Some(capabilities);
+            }
+            ServerMessage::ChatMessage(content) => {
+                let (id, message) = match content {
+                    ChatContent::Plain(text) => (None, text),
+                    ChatContent::WithId { id, text } => (Some(id), text),
+                };
+                let message = if self.settings.strip_control_sequences {
+                    strip_control_sequences(&message)
+                } else {
+                    message
+                };
+                if let Some(name) = sender_name(&message) {
+                    self.seen_names.insert(name.to_owned());
+                }
+                self.messages.push(ChatEntry {
+                    id,
+                    ..ChatEntry::new(message)
+                });
+            }
+            ServerMessage::Reaction { message_id, emote } => {
+                *self
+                    .reactions
+                    .entry(message_id)
+                    .or_default()
+                    .entry(emote)
+                    .or_default() += 1;
+            }
+            ServerMessage::FinishedGame => {
+                #[cfg(feature = "discord")]
+                {
+                    self.rounds_played = 0;
                 }
-                ServerMessage::FinishedGame => {
-                    self.timer_finish_time = None;
-                    self.status_text = "Waiting Round Start!".to_owned();
-                    self.word_box = String::new();
+                self.is_ready = false;
+                self.timer_finish_time = None;
+                self.round_start_delay = None;
+                self.status_text = self.settings.waiting_round_guide.clone();
+                self.revealed_answers.clear();
+                self.round_guesses.clear();
+                self.word_box = String::new();
+            }
+            ServerMessage::FinishedRoundInfo {
+                word_answer,
+                to_next_round_time,
+            } => {
+                let to_next_round_time =
+                    match OffsetDateTime::parse(&to_next_round_time, &Iso8601::DEFAULT) {
+                        Ok(time) => time,
+                        Err(err) => {
+                            self.log_connection_event(format!(
+                                "Skipped frame ({err}): to_next_round_time {to_next_round_time}"
+                            ));
+                            return;
+                        }
+                    };
+                // an older round's result arriving late, also after the round following it
+                if self
+                    .latest_next_round_time
+                    .is_some_and(|latest| to_next_round_time <= latest)
+                    || self
+                        .stale_result_time
+                        .is_some_and(|stale| to_next_round_time <= stale)
+                {
+                    return;
                 }
-                ServerMessage::FinishedRoundInfo {
-                    word_answer,
-                    to_next_round_time,
-                } => {
-                    self.timer_finish_time = Some(
-                        OffsetDateTime::parse(&to_next_round_time, &Iso8601::DEFAULT).unwrap(),
-                    );
-                    self.status_text = "Time's up! The answer is:".to_owned();
-                    self.word_box = word_answer;
+
+                self.latest_next_round_time = Some(to_next_round_time);
+                self.timer_finish_time = Some(to_next_round_time);
+                self.status_text = self.settings.finished_round_guide.clone();
+                self.revealed_answers = word_answer.into();
+                self.answers_revealed_at = Some(Instant::now());
+                self.word_box = String::new();
+                if self.settings.clear_correct_guess {
+                    self.clear_correct_guess();
                 }
-                ServerMessage::OngoingRoundInfo {
-                    word_to_guess,
-                    round_finish_time,
-                } => {
-                    self.timer_finish_time =
-                        Some(OffsetDateTime::parse(&round_finish_time, &Iso8601::DEFAULT).unwrap());
-                    self.status_text = "Please guess:".to_owned();
-                    self.word_box = word_to_guess;
+            }
+            ServerMessage::OngoingRoundInfo {
+                word_to_guess,
+                round_finish_time,
+            } => {
+                let round_finish_time =
+                    match OffsetDateTime::parse(&round_finish_time, &Iso8601::DEFAULT) {
+                        Ok(time) => time,
+                        Err(err) => {
+                            self.log_connection_event(format!(
+                                "Skipped frame ({err}): round_finish_time {round_finish_time}"
+                            ));
+                            return;
+                        }
+                    };
+                // a round that was already shown, or one that should have ended before the
+                // latest round result's break
+                if self
+                    .latest_round_finish_time
+                    .is_some_and(|latest| round_finish_time <= latest)
+                    || self
+                        .latest_next_round_time
+                        .is_some_and(|latest| round_finish_time <= latest)
+                {
+                    return;
                 }
-            },
+
+                // the previous result announced when this round would start, anything after
+                // that is network delay (plus any difference between the two clocks)
+                self.round_start_delay = self
+                    .latest_next_round_time
+                    .filter(|_| self.practice.is_none())
+                    .map(|scheduled_start| OffsetDateTime::now_utc() - scheduled_start);
+                // a server's results only come after its rounds finish, while practice ends
+                // rounds early, comparing with the clock its timestamps come from
+                self.stale_result_time = Some(if self.practice.is_some() {
+                    OffsetDateTime::now_utc()
+                } else {
+                    round_finish_time
+                });
+                self.latest_round_finish_time = Some(round_finish_time);
+                #[cfg(feature = "discord")]
+                {
+                    self.rounds_played += 1;
+                }
+                self.timer_finish_time = Some(round_finish_time);
+                self.status_text = self.settings.ongoing_round_guide.clone();
+                self.revealed_answers.clear();
+                self.round_guesses.clear();
+                self.word_box = word_to_guess;
+            }
+        }
+    }
+
+    fn connection_lost(&mut self, reason: String) {
+        self.log_connection_event(format!("Connection lost: {reason}"));
+        self.ready_before_drop = self.is_ready;
+        self.close_websocket();
+
+        // only reached for connections that weren't closed on purpose
+        if self.settings.alert_on_disconnect {
+            self.disconnect_alert_at = Some(Instant::now());
+        }
+
+        // shown as a banner instead of an error window so the game stays visible
+        self.connection_lost_reason = Some(reason);
+        if self.settings.auto_reconnect {
+            self.schedule_reconnect();
+        }
+    }
+
+    fn schedule_reconnect(&mut self) {
+        let attempts_left = self
+            .settings
+            .reconnect_attempt_limit
+            .is_none_or(|limit| self.reconnect_attempts < limit);
+
+        if attempts_left {
+            self.reconnect_at = Some(Instant::now() + RECONNECT_DELAY);
+        } else {
+            self.log_connection_event(format!(
+                "Gave up reconnecting after {} attempts",
+                self.reconnect_attempts
+            ));
+            self.gave_up_reconnecting = true;
+        }
+    }
+
+    fn reconnect(&mut self, ctx: &Context) {
+        self.reconnect_at = None;
+        self.reconnect_attempts += 1;
+        self.log_connection_event(format!("Reconnect attempt {}", self.reconnect_attempts));
+
+        // each attempt moves on to the next URL after the one that was last connected
+        let urls = self.failover_urls();
+        let start = urls
+            .iter()
+            .position(|url| *url == self.connected_url)
+            .unwrap_or(0);
+        let url = urls[(start + self.reconnect_attempts as usize) % urls.len()].clone();
+        self.log_connection_event(format!("Trying {url}"));
+
+        // a server that doesn't answer would freeze the window until the connection times out
+        let (sender, receiver) = mpsc::channel();
+        let thread_url = url.clone();
+        let thread_ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(connect(&thread_url, thread_ctx.clone()));
+            thread_ctx.request_repaint();
+        });
+        self.pending_reconnect = Some((url, receiver));
+    }
+
+    fn reconnect_finished(&mut self, url: String, result: Result<ChannelWebsocket, String>) {
+        match result {
+            Ok(websocket) => {
+                self.close_open_websocket();
+                self.connection_opened(url, websocket);
+            }
             Err(err) => {
-                self.err_texts.push(err);
+                self.log_connection_event(format!("Reconnect failed: {err}"));
+                self.schedule_reconnect();
             }
         }
     }
 
+    /// The primary URL followed by the configured fallbacks
+    fn failover_urls(&self) -> Vec<String> {
+        let mut urls = vec![self.primary_url.clone()];
+        for url in self.settings.fallback_urls() {
+            if !urls.iter().any(|known| known == url) {
+                urls.push(url.to_owned());
+            }
+        }
+        urls
+    }
+
+    /// Time left until an idle connection is closed, `None` when there's no idle timeout
+    fn idle_time_left(&self) -> Option<Duration> {
+        let timeout = Duration::from_secs(u64::from(self.settings.idle_disconnect_minutes?) * 60);
+        self.websocket.as_ref()?;
+        let idle_time = self.last_interaction_at?.elapsed();
+        Some(timeout.saturating_sub(idle_time))
+    }
+
+    fn idle_timeout_reached(&mut self) {
+        let minutes = self.settings.idle_disconnect_minutes.unwrap_or_default();
+        self.disconnect_button_clicked();
+        let reason = format!("Disconnected after {minutes} minutes without activity");
+        self.log_connection_event(reason.clone());
+        // the banner offers to reconnect, its reconnect timer isn't started
+        self.connection_lost_reason = Some(reason);
+    }
+
+    fn cancel_reconnect(&mut self) {
+        // a connection the worker thread still opens is dropped, and closed, once it's done
+        self.pending_reconnect = None;
+        self.reconnect_at = None;
+        self.reconnect_attempts = 0;
+        self.gave_up_reconnecting = false;
+    }
+
+    fn log_connection_event(&mut self, event: String) {
+        if self.connection_log.len() == CONNECTION_LOG_CAPACITY {
+            self.connection_log.pop_front();
+        }
+        self.connection_log
+            .push_back((OffsetDateTime::now_utc(), event));
+    }
+
+    /// Dropping the sender stops the reader thread, which closes the socket
+    fn close_websocket(&mut self) {
+        self.websocket = None;
+        self.is_ready = false;
+        self.server_capabilities = None;
+    }
+
+    /// A reconnect can race a connection that is still open, don't leave its thread running
+    fn close_open_websocket(&mut self) {
+        if self.websocket.is_some() {
+            self.log_connection_event(format!("Closing the connection to {}", self.connected_url));
+            self.close_websocket();
+        }
+    }
+
+    fn connect_to(&mut self, url: String, ctx: &Context) -> Result<(), String> {
+        self.close_open_websocket();
+        let websocket = connect(&url, ctx.clone())?;
+        self.connection_opened(url, websocket);
+        Ok(())
+    }
+
+    fn connection_opened(&mut self, url: String, websocket: ChannelWebsocket) {
+        self.reset_round_order();
+
+        self.log_connection_event(format!("Connected to {url}"));
+        self.connection_lost_reason = None;
+        self.connected_url = url;
+        self.websocket = Some(websocket);
+        self.last_interaction_at = Some(Instant::now());
+        self.reconnect_attempts = 0;
+        self.server_capabilities = None;
+        if self.settings.negotiate_features {
+            self.send(ClientMessage::Hello {
+                features: CLIENT_FEATURES
+                    .iter()
+                    .map(|&feature| feature.to_owned())
+                    .collect(),
+            });
+        }
+        // a new connection is a new session for the server, so restore what the player had set up
+        let replay_ready = self.settings.replay_ready && self.ready_before_drop;
+        self.ready_before_drop = false;
+        if self.settings.auto_send_ready || replay_ready {
+            self.send_ready();
+        }
+    }
+
     fn connect_button_clicked(&mut self, ctx: &Context) {
-        match connect(&self.server_url, ctx.clone()) {
-            Ok(websocket) => self.websocket = Some(websocket),
-            Err(err) => {
+        self.cancel_reconnect();
+        self.ready_before_drop = false;
+        self.primary_url = self.server_url.clone();
+        if let Err(err) = self.connect_to(self.server_url.clone(), ctx) {
+            self.log_connection_event(format!("Failed to connect to {}: {err}", self.server_url));
+            if self.settings.auto_reconnect && self.settings.fallback_urls().next().is_some() {
+                // go through the fallbacks like after a dropped connection
+                self.connected_url = self.server_url.clone();
+                self.connection_lost_reason = Some(err);
+                self.schedule_reconnect();
+            } else {
                 self.err_texts.push(err);
             }
         }
     }
 
+    fn reconnect_button_clicked(&mut self, ctx: &Context) {
+        self.cancel_reconnect();
+        let url = self.connected_url.clone();
+        if let Err(err) = self.connect_to(url, ctx) {
+            self.log_connection_event(format!("Reconnect failed: {err}"));
+            self.connection_lost_reason = Some(err);
+        }
+    }
+
     fn disconnect_button_clicked(&mut self) {
-        self.websocket = None;
+        self.cancel_reconnect();
+        self.connection_lost_reason = None;
+        self.ready_before_drop = false;
+        self.log_connection_event("Disconnected".to_owned());
+        self.close_websocket();
+    }
+
+    fn practice_button_clicked(&mut self) {
+        let (practice, first_round) = PracticeSession::start();
+        self.reset_round_order();
+        self.practice = Some(practice);
+        self.messages
+            .push(ChatEntry::new("——— Practicing offline ———".to_owned()));
+        self.server_message_received(first_round);
+    }
+
+    fn stop_practice_button_clicked(&mut self) {
+        self.practice = None;
+        self.server_message_received(ServerMessage::FinishedGame);
+    }
+
+    fn switch_server_confirmed(&mut self, ctx: &Context) {
+        self.show_switch_confirmation = false;
+        self.disconnect_button_clicked();
+
+        // the previous server's round doesn't carry over
+        self.timer_finish_time = None;
+        self.status_text = String::new();
+        self.revealed_answers.clear();
+        self.round_guesses.clear();
+        self.word_box = String::new();
+
+        self.messages.push(ChatEntry::new(format!(
+            "——— Switched to {} ———",
+            self.server_url
+        )));
+        self.connect_button_clicked(ctx);
+    }
+
+    /// Sends a message to the connected server, or only logs it in a dry run
+    fn send(&mut self, message: ClientMessage) -> Delivery {
+        if self.dry_run {
+            self.log_connection_event(format!("Dry run, not sent: {}", message.to_frame()));
+            return Delivery::Logged;
+        }
+
+        let Some((sender, _)) = &self.websocket else {
+            return Delivery::Failed;
+        };
+
+        match sender.send(message.to_frame()) {
+            Ok(()) => Delivery::Sent,
+            Err(err) => {
+                self.err_texts.push(err.to_string());
+                Delivery::Failed
+            }
+        }
+    }
+
+    /// Whether the server announced `feature`, assuming it does when it never replied
+    fn server_supports(&self, feature: &str) -> bool {
+        self.server_capabilities
+            .as_ref()
+            .is_none_or(|capabilities| capabilities.iter().any(|supported| supported == feature))
+    }
+
+    fn send_ready(&mut self) {
+        // defaults to `ClientMessage::Ready`, some servers expect a different message
+        let ready = ClientMessage::Raw(self.settings.ready_message.clone());
+        // a logged ready doesn't make the player ready, nor is it replayed after reconnecting
+        if self.send(ready) == Delivery::Sent {
+            self.is_ready = true;
+        }
+    }
+
+    /// Sends a chat message or guess, or hands it to the practice session when practicing
+    fn submit_message(&mut self, message: String) {
+        if message.is_empty() {
+            return;
+        }
+
+        if let Some(practice) = &mut self.practice {
+            for reply in practice.guess(&message) {
+                self.server_message_received(reply);
+            }
+        } else if self.send(ClientMessage::Chat(message.clone())) == Delivery::Failed {
+            return;
+        }
+
+        self.round_guesses.push(message.clone());
+        if self.sent_history.len() == SENT_HISTORY_CAPACITY {
+            self.sent_history.remove(0);
+        }
+        self.sent_history.push(message);
+    }
+
+    fn can_resend(&self) -> bool {
+        (self.websocket.is_some() || self.practice.is_some()) && !self.sent_history.is_empty()
+    }
+
+    fn resend_last_triggered(&mut self) {
+        if !self.can_resend() {
+            return;
+        }
+        if let Some(message) = self.sent_history.last().cloned() {
+            self.submit_message(message);
+        }
+    }
+
+    fn reactions_for(&self, entry: &ChatEntry) -> Option<&BTreeMap<String, u32>> {
+        self.reactions.get(entry.id.as_ref()?)
+    }
+
+    /// Names matching the word being typed, either after an `@` or long enough on its own
+    fn name_suggestions(&self) -> Vec<String> {
+        let word = self.message_to_send.rsplit(' ').next().unwrap_or_default();
+        let partial = match word.strip_prefix('@') {
+            Some(partial) => partial,
+            None if word.chars().count() >= NAME_SUGGESTION_MIN_LENGTH => word,
+            None => return Vec::new(),
+        }
+        .to_lowercase();
+
+        self.seen_names
+            .iter()
+            .filter(|name| {
+                let name = name.to_lowercase();
+                name.starts_with(&partial) && name != partial
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Replaces the word being typed with `name`, keeping its `@`
+    fn name_suggestion_picked(&mut self, name: &str) {
+        let word_start = self.message_to_send.rfind(' ').map_or(0, |idx| idx + 1);
+        let mention = if self.message_to_send[word_start..].starts_with('@') {
+            "@"
+        } else {
+            ""
+        };
+        self.message_to_send.truncate(word_start);
+        self.message_to_send.push_str(&format!("{mention}{name} "));
+        self.name_suggestion_idx = 0;
     }
 
     fn message_field_submitted(&mut self, message_field: &Response) {
-        if let Some((sender, _)) = &self.websocket {
-            if !self.message_to_send.is_empty() {
-                if let Err(err) = sender.send(self.message_to_send.clone()) {
-                    self.err_texts.push(err.to_string());
+        let message = std::mem::take(&mut self.message_to_send);
+        self.submit_message(message);
+        message_field.request_focus();
+    }
+
+    fn word_box_section(&self, ui: &mut Ui) {
+        let seconds_left = self
+            .timer_finish_time
+            .map(|time| (time - OffsetDateTime::now_utc()).as_seconds_f32().round());
+        ui.label(format!(
+            "{} {}",
+            self.status_text,
+            seconds_left.map_or(String::new(), |seconds| format!("{seconds} seconds"))
+        ))
+        .widget_info(|| {
+            let label = match seconds_left {
+                Some(seconds) => format!("{}, time left: {seconds} seconds", self.status_text),
+                None => self.status_text.clone(),
+            };
+            WidgetInfo::labeled(WidgetType::Label, label)
+        });
+
+        if let Some(delay) = self
+            .round_start_delay
+            .filter(|_| self.revealed_answers.is_empty())
+        {
+            ui.label(
+                RichText::new(if delay.is_negative() {
+                    format!("Round arrived {} ms early", -delay.whole_milliseconds())
+                } else {
+                    format!("Round arrived {} ms late", delay.whole_milliseconds())
+                })
+                .small()
+                .weak(),
+            )
+            .on_hover_text(
+                "Time between the announced round start and receiving the round. \
+                 A difference between your clock and the server's adds to it.",
+            );
+        }
+
+        if self.revealed_answers.is_empty() {
+            word_box_ui(ui, &self.word_box, &self.settings, false).widget_info(|| {
+                WidgetInfo::labeled(
+                    WidgetType::Label,
+                    format!("current word: {}", spelled_out(&self.word_box)),
+                )
+            });
+            return;
+        }
+
+        let revealed_letters = self.revealed_letter_count();
+        let show_answer = |ui: &mut Ui, answer: &String| {
+            // the full answer straight away, the letter by letter reveal is only visual
+            word_box_ui(
+                ui,
+                &reveal_letters(answer, revealed_letters),
+                &self.settings,
+                self.was_guessed(answer),
+            )
+            .widget_info(|| {
+                WidgetInfo::labeled(
+                    WidgetType::Label,
+                    format!("answer: {}", spelled_out(answer)),
+                )
+            });
+        };
+        match self.settings.word_box_alignment {
+            WordBoxAlignment::Left => {
+                ui.horizontal_wrapped(|ui| {
+                    for answer in &self.revealed_answers {
+                        show_answer(ui, answer);
+                    }
+                });
+            }
+            // a wrapped row can't be centered, so centered answers go one per line
+            WordBoxAlignment::Center => {
+                for answer in &self.revealed_answers {
+                    show_answer(ui, answer);
                 }
             }
         }
 
-        self.message_to_send = String::new();
-        message_field.request_focus();
+        if self
+            .revealed_answers
+            .iter()
+            .any(|answer| self.was_guessed(answer))
+        {
+            ui.label(RichText::new("🎉 You got it!").color(Color32::from_rgb(120, 220, 120)));
+        }
+    }
+
+    fn revealed_letter_count(&self) -> usize {
+        match self.answers_revealed_at {
+            Some(revealed_at) if !self.settings.reduce_motion => {
+                (revealed_at.elapsed().as_millis() / REVEAL_LETTER_INTERVAL.as_millis()) as usize
+                    + 1
+            }
+            _ => usize::MAX,
+        }
+    }
+
+    fn was_guessed(&self, answer: &str) -> bool {
+        self.round_guesses
+            .iter()
+            .any(|guess| is_same_word(guess, answer))
+    }
+
+    /// Round timestamps from a different server or practice session aren't comparable
+    fn reset_round_order(&mut self) {
+        self.latest_next_round_time = None;
+        self.latest_round_finish_time = None;
+        self.round_start_delay = None;
+        self.stale_result_time = None;
+        #[cfg(feature = "discord")]
+        {
+            self.rounds_played = 0;
+        }
+    }
+
+    #[cfg(feature = "discord")]
+    fn update_presence(&mut self) {
+        let client_id = self.settings.discord_client_id.trim();
+        if !self.settings.discord_presence || client_id.is_empty() {
+            // closing the connection to Discord clears the presence
+            self.presence = None;
+            return;
+        }
+        if self
+            .presence
+            .as_ref()
+            .is_none_or(|presence| presence.client_id() != client_id)
+        {
+            self.presence = Some(presence::DiscordPresence::start(client_id.to_owned()));
+        }
+
+        let details = if self.practice.is_some() {
+            "Practicing offline".to_owned()
+        } else if self.rounds_played > 0 {
+            format!("Round {}", self.rounds_played)
+        } else {
+            "Waiting for a round".to_owned()
+        };
+        let state = if self.timer_finish_time.is_none() {
+            "Waiting for the round to start"
+        } else if self.revealed_answers.is_empty() {
+            "Guessing a word"
+        } else {
+            "Between rounds"
+        };
+        let activity =
+            (self.websocket.is_some() || self.practice.is_some()).then(|| presence::Activity {
+                details,
+                state: state.to_owned(),
+                end_time: self.timer_finish_time.map(OffsetDateTime::unix_timestamp),
+            });
+
+        if let Some(presence) = &mut self.presence {
+            presence.update(activity);
+        }
+    }
+
+    fn clear_correct_guess(&mut self) {
+        // only clear the field if it still holds the answer, not something newly typed
+        if self
+            .revealed_answers
+            .iter()
+            .any(|answer| is_same_word(&self.message_to_send, answer))
+        {
+            self.message_to_send = String::new();
+        }
+    }
+
+    fn apply_theme(&mut self, ctx: &Context) {
+        self.theme = Theme::load(&self.settings.theme_path).unwrap_or_else(|err| {
+            self.err_texts
+                .push(format!("Couldn't load theme, using the default one. {err}"));
+            Theme::default()
+        });
+        ctx.set_style(create_app_style(&self.theme, ctx.pixels_per_point()));
+    }
+
+    fn whats_new_closed(&mut self) {
+        self.show_whats_new = false;
+    }
+
+    fn focus_mode_toggled(&mut self) {
+        self.focus_mode = !self.focus_mode;
+        if self.focus_mode {
+            self.messages_before_focus = Some(self.messages.len());
+        } else {
+            self.unread_from_focus = self.unread_count();
+            self.messages_before_focus = None;
+        }
+    }
+
+    /// Messages that arrived since focus mode was entered
+    fn unread_count(&self) -> usize {
+        self.messages_before_focus
+            .map_or(0, |before| self.messages.len().saturating_sub(before))
     }
 
     fn close_err_button_clicked(&mut self, idx: usize) {
@@ -232,13 +1364,99 @@ impl WordgamesClient {
 }
 
 impl eframe::App for WordgamesClient {
+    /// Called by eframe periodically and on exit, the only place anything is persisted
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(
+            SETTINGS_KEY,
+            serde_json::to_string(&self.settings).expect("settings always serialize to JSON"),
+        );
+        storage.set_string(
+            SENT_HISTORY_KEY,
+            serde_json::to_string(&self.sent_history).expect("strings always serialize to JSON"),
+        );
+        storage.set_string(SERVER_URL_KEY, self.server_url.clone());
+        // the changelog was either closed or already seen before this run
+        if !self.show_whats_new {
+            storage.set_string(LAST_SEEN_VERSION_KEY, APP_VERSION.to_owned());
+        }
+    }
+
     fn update(&mut self, ctx: &Context, _: &mut eframe::Frame) {
-        // fetch message and errors from reader thread
-        if let Some((_, receiver)) = &self.websocket {
-            if let Ok(result) = receiver.try_recv() {
-                self.ws_result_received(result);
+        // moving the window to a monitor with another scale changes how wide a pixel is
+        let pixels_per_point = ctx.pixels_per_point();
+        if pixels_per_point != self.styled_pixels_per_point {
+            self.styled_pixels_per_point = pixels_per_point;
+            ctx.set_style(create_app_style(&self.theme, pixels_per_point));
+        }
+
+        if let Some(reconnect_at) = self.reconnect_at {
+            let now = Instant::now();
+            if now >= reconnect_at {
+                self.reconnect(ctx);
+            } else {
+                ctx.request_repaint_after(reconnect_at - now);
             }
         }
+        let reconnect_result =
+            self.pending_reconnect
+                .as_ref()
+                .and_then(|(_, receiver)| match receiver.try_recv() {
+                    Ok(result) => Some(result),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => {
+                        Some(Err("Connecting stopped unexpectedly".to_owned()))
+                    }
+                });
+        if let Some(result) = reconnect_result {
+            if let Some((url, _)) = self.pending_reconnect.take() {
+                self.reconnect_finished(url, result);
+            }
+        }
+
+        // fetch message and errors from reader thread.
+        // drain everything that arrived since the last frame so that quick round transitions
+        // are applied in order and the UI ends up in the latest state
+        let results: Vec<_> = self
+            .websocket
+            .as_ref()
+            .map_or_else(Vec::new, |(_, receiver)| receiver.try_iter().collect());
+        for result in results {
+            self.ws_result_received(result);
+        }
+
+        if let Some(message) = self.practice.as_mut().and_then(PracticeSession::tick) {
+            self.server_message_received(message);
+        }
+
+        // any key press, click or pointer movement over the window counts as activity
+        if ctx.input(|i| !i.events.is_empty()) {
+            self.last_interaction_at = Some(Instant::now());
+        }
+        if self.idle_time_left() == Some(Duration::ZERO) {
+            self.idle_timeout_reached();
+        }
+
+        #[cfg(feature = "discord")]
+        self.update_presence();
+
+        let longest_answer = self
+            .revealed_answers
+            .iter()
+            .map(|answer| answer.chars().count())
+            .max()
+            .unwrap_or(0);
+        if self.revealed_letter_count() < longest_answer {
+            ctx.request_repaint_after(REVEAL_LETTER_INTERVAL);
+        }
+        let animate_messages = self.settings.animate_new_messages && !self.settings.reduce_motion;
+        if animate_messages
+            && self
+                .messages
+                .last()
+                .is_some_and(|entry| entry.arrival_progress() < 1.0)
+        {
+            ctx.request_repaint();
+        }
 
         // UI
         for (idx, err_text) in self.err_texts.clone().iter().enumerate() {
@@ -253,77 +1471,801 @@ impl eframe::App for WordgamesClient {
                 });
         }
 
-        TopBottomPanel::bottom("bottom_panel")
-            .frame(Frame {
-                inner_margin: Margin::same(12.0),
-                ..Frame::side_top_panel(&ctx.style())
-            })
-            .show_separator_line(false)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("Message:");
+        if self.show_whats_new {
+            Window::new(format!("What's new in {APP_VERSION}"))
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        ui.label(changelog_section(CHANGELOG, APP_VERSION));
+                    });
+                    if ui.button("Close").clicked() {
+                        self.whats_new_closed();
+                    }
+                });
+        }
 
-                    ui.centered_and_justified(|ui| {
-                        let message_field = ui.text_edit_singleline(&mut self.message_to_send);
-                        if message_field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
-                            self.message_field_submitted(&message_field);
+        if self.show_switch_confirmation {
+            Window::new("Switch server")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Disconnect from {} and connect to {}?",
+                        self.connected_url, self.server_url
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Switch").clicked() {
+                            self.switch_server_confirmed(ctx);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_switch_confirmation = false;
                         }
                     });
                 });
+        }
+
+        if hotkeys::TOGGLE_FOCUS_MODE.consume(ctx) {
+            self.focus_mode_toggled();
+        }
+        if hotkeys::RESEND_LAST.consume(ctx) {
+            self.resend_last_triggered();
+        }
+        if hotkeys::TOGGLE_SHORTCUTS.consume(ctx) {
+            self.show_shortcuts = !self.show_shortcuts;
+        }
+        if self.show_shortcuts && hotkeys::CLOSE_SHORTCUTS.consume(ctx) {
+            self.show_shortcuts = false;
+        }
+        Window::new("Keyboard shortcuts")
+            .open(&mut self.show_shortcuts)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                Grid::new("shortcuts_grid").striped(true).show(ui, |ui| {
+                    for hotkey in hotkeys::HOTKEYS {
+                        ui.label(RichText::new(ctx.format_shortcut(&hotkey.shortcut)).code());
+                        ui.label(hotkey.description);
+                        ui.end_row();
+                    }
+                });
             });
 
-        CentralPanel::default()
-            .frame(Frame {
-                inner_margin: Margin {
-                    left: 12.0,
-                    right: 12.0,
-                    top: 12.0,
-                    bottom: 0.0,
-                },
-                ..Frame::central_panel(&ctx.style())
-            })
+        let mut settings_action = None;
+        Window::new("Settings")
+            .open(&mut self.show_settings)
+            .resizable(false)
             .show(ctx, |ui| {
-                ui.add_enabled_ui(self.websocket.is_none(), |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Server URL:");
-                        ui.centered_and_justified(|ui| {
-                            ui.text_edit_singleline(&mut self.server_url);
-                        });
+                settings_action = self.settings.show(ui);
+            });
+        match settings_action {
+            Some(SettingsAction::ApplyTheme) => self.apply_theme(ctx),
+            Some(SettingsAction::TestDisconnectAlert) => {
+                self.disconnect_alert_at = Some(Instant::now());
+            }
+            None => (),
+        }
+
+        if let Some(time_left) = self
+            .idle_time_left()
+            .filter(|&time_left| time_left <= IDLE_WARNING_TIME)
+        {
+            Window::new("Still there?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-12.0, -60.0))
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Disconnecting in {} seconds because of inactivity.",
+                        time_left.as_secs_f32().ceil()
+                    ));
+                    if ui.button("Stay connected").clicked() {
+                        self.last_interaction_at = Some(Instant::now());
+                    }
+                });
+        }
+
+        Window::new("Connection log")
+            .open(&mut self.show_connection_log)
+            .show(ctx, |ui| {
+                if ui.button("Clear").clicked() {
+                    self.connection_log.clear();
+                }
+                ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (time, event) in &self.connection_log {
+                            ui.label(format!("[{}] {event}", format_timestamp(*time)));
+                        }
                     });
-                    ui.vertical_centered_justified(|ui| {
-                        if ui.button("Connect").clicked() {
-                            self.connect_button_clicked(ctx);
+            });
+
+        if !self.focus_mode {
+            TopBottomPanel::top("top_panel")
+                .frame(Frame {
+                    inner_margin: Margin::same(12.0),
+                    ..Frame::side_top_panel(&ctx.style())
+                })
+                .show_separator_line(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.toggle_value(&mut self.show_settings, "Settings");
+                        ui.toggle_value(&mut self.show_connection_log, "Connection log");
+                        if ui
+                            .button(format!(
+                                "Focus mode ({})",
+                                ctx.format_shortcut(&hotkeys::TOGGLE_FOCUS_MODE.shortcut)
+                            ))
+                            .clicked()
+                        {
+                            self.focus_mode_toggled();
                         }
+                        ui.toggle_value(&mut self.dry_run, "Dry run").on_hover_text(
+                            "Write what would be sent to the connection log instead of sending it",
+                        );
+                        ui.toggle_value(
+                            &mut self.show_shortcuts,
+                            format!(
+                                "Shortcuts ({})",
+                                ctx.format_shortcut(&hotkeys::TOGGLE_SHORTCUTS.shortcut)
+                            ),
+                        );
                     });
                 });
-                ui.add_enabled_ui(self.websocket.is_some(), |ui| {
-                    ui.vertical_centered_justified(|ui| {
-                        if ui.button("Disconnect").clicked() {
-                            self.disconnect_button_clicked();
+        }
+
+        // easy to forget about otherwise, and guesses would seem to go nowhere
+        if self.dry_run {
+            TopBottomPanel::top("dry_run_banner")
+                .frame(Frame {
+                    inner_margin: Margin::same(8.0),
+                    fill: Color32::from_rgb(110, 85, 20),
+                    ..Frame::side_top_panel(&ctx.style())
+                })
+                .show_separator_line(false)
+                .show(ctx, |ui| {
+                    ui.label(
+                        RichText::new(
+                            "DRY RUN — messages are written to the connection log, not sent",
+                        )
+                        .strong(),
+                    );
+                });
+        }
+
+        if let Some(reason) = self.connection_lost_reason.clone() {
+            TopBottomPanel::top("connection_lost_banner")
+                .frame(Frame {
+                    inner_margin: Margin::same(8.0),
+                    fill: Color32::from_rgb(90, 35, 35),
+                    ..Frame::side_top_panel(&ctx.style())
+                })
+                .show_separator_line(false)
+                .show(ctx, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        if let Some(reconnect_at) = self.reconnect_at {
+                            ui.label(format!(
+                                "Connection lost — reconnecting in {} seconds (attempt {})",
+                                reconnect_at
+                                    .saturating_duration_since(Instant::now())
+                                    .as_secs_f32()
+                                    .ceil(),
+                                self.reconnect_attempts + 1
+                            ));
+                            if ui.button("Cancel").clicked() {
+                                self.cancel_reconnect();
+                            }
+                        } else if let Some((url, _)) = &self.pending_reconnect {
+                            ui.label(format!(
+                                "Connection lost — trying {url} (attempt {})",
+                                self.reconnect_attempts
+                            ));
+                            if ui.button("Cancel").clicked() {
+                                self.cancel_reconnect();
+                            }
+                        } else {
+                            ui.label(format!("Connection lost: {reason}"));
+                            if self.gave_up_reconnecting {
+                                ui.label(format!(
+                                    "Gave up reconnecting after {} attempts.",
+                                    self.reconnect_attempts
+                                ));
+                            }
+                            if ui.button("Reconnect").clicked() {
+                                self.reconnect_button_clicked(ctx);
+                            }
+                            if ui.button("Dismiss").clicked() {
+                                self.connection_lost_reason = None;
+                            }
                         }
                     });
                 });
+        }
 
-                ui.label(&format!(
-                    "{} {}",
-                    self.status_text,
-                    self.timer_finish_time.map_or(String::new(), |time| format!(
-                        "{} seconds",
-                        (time - OffsetDateTime::now_utc()).as_seconds_f32().round()
-                    ))
-                ));
-                ui.label(RichText::new(&self.word_box).code().size(32.0));
-
-                ui.heading("Messages: ");
-                ScrollArea::vertical()
-                    .stick_to_bottom(true)
-                    .auto_shrink([false, true])
-                    .max_width(f32::INFINITY)
-                    .show(ui, |ui| {
-                        for message in &self.messages {
-                            ui.label(message);
-                        }
+        if !self.focus_mode {
+            TopBottomPanel::bottom("bottom_panel")
+                .frame(Frame {
+                    inner_margin: Margin::same(12.0),
+                    ..Frame::side_top_panel(&ctx.style())
+                })
+                .show_separator_line(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Message:");
+
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let send_clicked =
+                                !self.settings.send_on_enter && ui.button("Send").clicked();
+                            if ui
+                                .add_enabled(self.can_resend(), Button::new("Resend"))
+                                .on_hover_text(format!(
+                                    "Send the last message again ({})",
+                                    ctx.format_shortcut(&hotkeys::RESEND_LAST.shortcut)
+                                ))
+                                .clicked()
+                            {
+                                self.resend_last_triggered();
+                            }
+
+                            ui.centered_and_justified(|ui| {
+                                let message_field_id = Id::new("message_field");
+                                let suggestions = if ui.memory(|m| m.has_focus(message_field_id)) {
+                                    self.name_suggestions()
+                                } else {
+                                    Vec::new()
+                                };
+                                let mut picked_name = None;
+                                if !suggestions.is_empty() {
+                                    // taken before the field sees them
+                                    if hotkeys::NEXT_NAME.consume(ctx) {
+                                        self.name_suggestion_idx += 1;
+                                    }
+                                    if hotkeys::PREVIOUS_NAME.consume(ctx) {
+                                        self.name_suggestion_idx = self
+                                            .name_suggestion_idx
+                                            .checked_sub(1)
+                                            .unwrap_or(suggestions.len() - 1);
+                                    }
+                                    self.name_suggestion_idx %= suggestions.len();
+                                    if hotkeys::COMPLETE_NAME.consume(ctx) {
+                                        picked_name =
+                                            Some(suggestions[self.name_suggestion_idx].clone());
+                                    }
+                                }
+
+                                let message_field = ui.add(
+                                    TextEdit::singleline(&mut self.message_to_send)
+                                        .id(message_field_id)
+                                        // egui moves focus on Tab before any widget runs,
+                                        // consuming it above doesn't keep the field focused
+                                        .lock_focus(!suggestions.is_empty()),
+                                );
+
+                                // the field sits at the bottom of the window, so the list opens above it
+                                if !suggestions.is_empty() {
+                                    Area::new("name_suggestions")
+                                        .order(Order::Foreground)
+                                        .fixed_pos(message_field.rect.left_top())
+                                        .pivot(Align2::LEFT_BOTTOM)
+                                        .show(ctx, |ui| {
+                                            Frame::popup(ui.style()).show(ui, |ui| {
+                                                for (idx, name) in suggestions.iter().enumerate() {
+                                                    let selected = idx == self.name_suggestion_idx;
+                                                    if ui.selectable_label(selected, name).clicked()
+                                                    {
+                                                        picked_name = Some(name.clone());
+                                                    }
+                                                }
+                                            });
+                                        });
+                                }
+                                if let Some(name) = picked_name {
+                                    self.name_suggestion_picked(&name);
+                                    let mut state = TextEditState::load(ctx, message_field_id)
+                                        .unwrap_or_default();
+                                    state.set_ccursor_range(Some(CCursorRange::one(CCursor::new(
+                                        self.message_to_send.chars().count(),
+                                    ))));
+                                    state.store(ctx, message_field_id);
+                                    message_field.request_focus();
+                                }
+                                let enter_pressed = message_field.lost_focus()
+                                    && ui.input(|i| {
+                                        i.key_pressed(hotkeys::SEND_MESSAGE.shortcut.key)
+                                    });
+
+                                if send_clicked || (enter_pressed && self.settings.send_on_enter) {
+                                    self.message_field_submitted(&message_field);
+                                } else if enter_pressed {
+                                    // Enter doesn't send in this mode, so keep typing in the field
+                                    message_field.request_focus();
+                                }
+                            });
+                        });
                     });
+                });
+        }
+
+        // only the word box and timer, while chat keeps arriving in the background
+        if self.focus_mode {
+            CentralPanel::default().show(ctx, |ui| {
+                let word_box_layout = match self.settings.word_box_alignment {
+                    WordBoxAlignment::Left => Layout::top_down(Align::Min),
+                    WordBoxAlignment::Center => Layout::top_down(Align::Center),
+                };
+                ui.with_layout(word_box_layout, |ui| {
+                    ui.add_space(ui.available_height() / 4.0);
+                    self.word_box_section(ui);
+                    ui.add_space(24.0);
+                    let unread = self.unread_count();
+                    if unread > 0 {
+                        ui.weak(format!("{unread} unread messages"));
+                    }
+                    if ui
+                        .small_button(format!(
+                            "Leave focus mode ({})",
+                            ctx.format_shortcut(&hotkeys::TOGGLE_FOCUS_MODE.shortcut)
+                        ))
+                        .clicked()
+                    {
+                        self.focus_mode_toggled();
+                    }
+                });
             });
+        } else {
+            CentralPanel::default()
+                .frame(Frame {
+                    inner_margin: Margin {
+                        left: 12.0,
+                        right: 12.0,
+                        top: 12.0,
+                        bottom: 0.0,
+                    },
+                    ..Frame::central_panel(&ctx.style())
+                })
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Server URL:");
+                        ui.centered_and_justified(|ui| {
+                            ui.text_edit_singleline(&mut self.server_url);
+                        });
+                    });
+                    ui.add_enabled_ui(self.websocket.is_none() && self.practice.is_none(), |ui| {
+                        ui.vertical_centered_justified(|ui| {
+                            if ui.button("Connect").clicked() {
+                                self.connect_button_clicked(ctx);
+                            }
+                            if ui.button("Practice offline").clicked() {
+                                self.practice_button_clicked();
+                            }
+                        });
+                    });
+                    if self.practice.is_some() {
+                        ui.vertical_centered_justified(|ui| {
+                            if ui.button("Stop practice").clicked() {
+                                self.stop_practice_button_clicked();
+                            }
+                        });
+                    }
+                    if self.websocket.is_some() && self.server_url != self.primary_url {
+                        ui.vertical_centered_justified(|ui| {
+                            if ui.button("Switch server").clicked() {
+                                self.show_switch_confirmation = true;
+                            }
+                        });
+                    }
+                    if self.websocket.is_some() {
+                        let status = if self.connected_url == self.primary_url {
+                            format!("Connected to {}", self.connected_url)
+                        } else {
+                            format!(
+                                "Connected to fallback {} ({} is unreachable)",
+                                self.connected_url, self.primary_url
+                            )
+                        };
+                        ui.label(&status).widget_info(|| {
+                            WidgetInfo::labeled(
+                                WidgetType::Label,
+                                format!("connection status: {status}"),
+                            )
+                        });
+                    }
+                    ui.add_enabled_ui(self.websocket.is_some(), |ui| {
+                        ui.vertical_centered_justified(|ui| {
+                            if ui.button("Disconnect").clicked() {
+                                self.disconnect_button_clicked();
+                            }
+                            if self.server_supports("ready") {
+                                let ready_button =
+                                    Button::new(if self.is_ready { "Ready ✔" } else { "Ready" });
+                                if ui.add_enabled(!self.is_ready, ready_button).clicked() {
+                                    self.send_ready();
+                                }
+                            }
+                        });
+                    });
+
+                    let word_box_layout = match self.settings.word_box_alignment {
+                        WordBoxAlignment::Left => Layout::top_down(Align::Min),
+                        WordBoxAlignment::Center => Layout::top_down(Align::Center),
+                    };
+                    ui.with_layout(word_box_layout, |ui| self.word_box_section(ui));
+
+                    ui.heading(format!("Messages ({}):", self.messages.len()));
+                    let unread = self.unread_from_focus;
+                    if unread > 0 {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{unread} new while in focus mode"));
+                            if ui.small_button("Dismiss").clicked() {
+                                self.unread_from_focus = 0;
+                            }
+                        });
+                    }
+                    match self.settings.chat_layout {
+                        ChatLayout::Full => {
+                            ScrollArea::vertical()
+                                .stick_to_bottom(true)
+                                .auto_shrink([false, true])
+                                .max_width(f32::INFINITY)
+                                .show(ui, |ui| {
+                                    for entry in &self.messages {
+                                        chat_entry_ui(
+                                            ui,
+                                            entry,
+                                            1.0,
+                                            animate_messages,
+                                            self.settings.chat_text_size,
+                                            self.reactions_for(entry),
+                                        );
+                                    }
+                                });
+                        }
+                        ChatLayout::Ticker => {
+                            let shown = self.messages.len().min(self.settings.ticker_length);
+                            for (age, entry) in self.messages[self.messages.len() - shown..]
+                                .iter()
+                                .rev()
+                                .enumerate()
+                                .rev()
+                            {
+                                // the newest message is the most visible, older ones fade out
+                                let opacity = 1.0 - age as f32 * 0.3;
+                                chat_entry_ui(
+                                    ui,
+                                    entry,
+                                    opacity,
+                                    animate_messages,
+                                    self.settings.chat_text_size,
+                                    self.reactions_for(entry),
+                                );
+                            }
+                        }
+                    }
+                });
+        }
+
+        // keystrokes won't reach the client, so show it over everything else
+        if self.settings.dim_when_unfocused && !ctx.input(|i| i.raw.has_focus) {
+            ctx.layer_painter(LayerId::new(
+                Order::Foreground,
+                Id::new("unfocused_overlay"),
+            ))
+            .rect(
+                ctx.screen_rect(),
+                0.0,
+                Color32::from_black_alpha(60),
+                Stroke::new(2.0, Color32::from_gray(90)),
+            );
+        }
+
+        if let Some(alert_at) = self.disconnect_alert_at {
+            let elapsed = alert_at.elapsed();
+            if elapsed < DISCONNECT_ALERT_DURATION {
+                // a steady red frame instead of flashing when motion is reduced
+                let fill = if self.settings.reduce_motion {
+                    Color32::TRANSPARENT
+                } else {
+                    let pulse =
+                        (elapsed.as_secs_f32() / DISCONNECT_ALERT_PULSE.as_secs_f32()).fract();
+                    Color32::from_rgba_unmultiplied(200, 40, 40, ((1.0 - pulse) * 90.0) as u8)
+                };
+                ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("disconnect_alert")))
+                    .rect(
+                        ctx.screen_rect(),
+                        0.0,
+                        fill,
+                        Stroke::new(6.0, Color32::from_rgb(200, 40, 40)),
+                    );
+                ctx.request_repaint();
+            } else {
+                self.disconnect_alert_at = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// A timestamp `seconds` from now, formatted like the server sends them
+    fn timestamp(seconds: i64) -> String {
+        (OffsetDateTime::now_utc() + time::Duration::seconds(seconds))
+            .format(&Iso8601::DEFAULT)
+            .unwrap()
+    }
+
+    fn finished_round(answer: &str, to_next_round_time: String) -> ServerMessage {
+        ServerMessage::FinishedRoundInfo {
+            word_answer: WordAnswer::Single(answer.to_owned()),
+            to_next_round_time,
+        }
+    }
+
+    fn ongoing_round(word: &str, round_finish_time: String) -> ServerMessage {
+        ServerMessage::OngoingRoundInfo {
+            word_to_guess: word.to_owned(),
+            round_finish_time,
+        }
+    }
+
+    #[test]
+    fn ready_is_tagged_json() {
+        assert_eq!(ClientMessage::Ready.to_frame(), r#"{"type":"Ready"}"#);
+    }
+
+    #[test]
+    fn hello_lists_features_as_content() {
+        let hello = ClientMessage::Hello {
+            features: vec!["ready".to_owned(), "reactions".to_owned()],
+        };
+        assert_eq!(
+            hello.to_frame(),
+            r#"{"type":"Hello","content":{"features":["ready","reactions"]}}"#
+        );
+    }
+
+    #[test]
+    fn chat_and_raw_are_sent_as_given() {
+        assert_eq!(ClientMessage::Chat("hello".to_owned()).to_frame(), "hello");
+        assert_eq!(
+            ClientMessage::Raw(r#"{"type":"Ready"}"#.to_owned()).to_frame(),
+            r#"{"type":"Ready"}"#
+        );
+    }
+
+    #[test]
+    fn dry_run_ready_is_not_replayed() {
+        let mut client = WordgamesClient {
+            dry_run: true,
+            websocket: Some((mpsc::channel().0, mpsc::channel().1)),
+            ..Default::default()
+        };
+        client.send_ready();
+        assert!(!client.is_ready);
+
+        client.connection_lost("Server closed the connection".to_owned());
+        assert!(!client.ready_before_drop);
+    }
+
+    #[test]
+    fn bad_timestamp_is_skipped() {
+        let mut client = WordgamesClient::default();
+        client.server_message_received(ongoing_round("dnoecs", "yesterday".to_owned()));
+        client.server_message_received(finished_round("second", "soon".to_owned()));
+
+        assert!(client.word_box.is_empty());
+        assert!(client.revealed_answers.is_empty());
+        assert_eq!(client.connection_log.len(), 2);
+    }
+
+    #[test]
+    fn unread_count_stops_when_focus_mode_is_left() {
+        let mut client = WordgamesClient::default();
+        client.focus_mode_toggled();
+        client.messages.push(ChatEntry::new("Alice: hi".to_owned()));
+        client
+            .messages
+            .push(ChatEntry::new("Bob: hello".to_owned()));
+        assert_eq!(client.unread_count(), 2);
+
+        client.focus_mode_toggled();
+        client
+            .messages
+            .push(ChatEntry::new("Alice: bye".to_owned()));
+        assert_eq!(client.unread_from_focus, 2);
+    }
+
+    #[test]
+    fn changelog_has_a_section_for_this_version() {
+        let section = changelog_section(CHANGELOG, APP_VERSION);
+        assert!(section.starts_with("- "));
+        assert!(!section.contains("## "));
+    }
+
+    #[test]
+    fn changelog_section_stops_at_the_next_version() {
+        let changelog = "# Changelog\n\n## 0.3.0\n\n- New\n\n## 0.2.0\n\n- Old\n";
+        assert_eq!(changelog_section(changelog, "0.3.0"), "- New");
+        assert_eq!(changelog_section(changelog, "0.2.0"), "- Old");
+        assert_eq!(changelog_section(changelog, "0.1.0"), "");
+    }
+
+    #[test]
+    fn late_result_of_the_previous_round_is_ignored() {
+        let mut client = WordgamesClient::default();
+        client.server_message_received(finished_round("first", timestamp(-60)));
+        client.server_message_received(ongoing_round("dnoecs", timestamp(-40)));
+        client.server_message_received(ongoing_round("driht", timestamp(30)));
+        client.server_message_received(finished_round("second", timestamp(-30)));
+
+        assert_eq!(client.word_box, "driht");
+        assert!(client.revealed_answers.is_empty());
+    }
+
+    #[test]
+    fn result_of_the_shown_round_is_accepted() {
+        let mut client = WordgamesClient::default();
+        client.server_message_received(finished_round("first", timestamp(-5)));
+        client.server_message_received(ongoing_round("dnoecs", timestamp(30)));
+        client.server_message_received(finished_round("second", timestamp(40)));
+
+        assert!(client.word_box.is_empty());
+        assert_eq!(client.revealed_answers, ["second"]);
+    }
+
+    #[test]
+    fn result_is_accepted_with_the_local_clock_ahead() {
+        let mut client = WordgamesClient::default();
+        // every server timestamp is two minutes behind the local clock
+        client.server_message_received(finished_round("first", timestamp(-125)));
+        client.server_message_received(ongoing_round("dnoecs", timestamp(-90)));
+        client.server_message_received(finished_round("second", timestamp(-85)));
+
+        assert!(client.word_box.is_empty());
+        assert_eq!(client.revealed_answers, ["second"]);
+    }
+
+    #[test]
+    fn practice_round_ended_early_is_accepted() {
+        let mut client = WordgamesClient {
+            practice: Some(PracticeSession::start().0),
+            ..Default::default()
+        };
+        client.server_message_received(ongoing_round("dnoecs", timestamp(30)));
+        // practice finishes a round as soon as it's guessed
+        client.server_message_received(finished_round("second", timestamp(3)));
+
+        assert!(client.word_box.is_empty());
+        assert_eq!(client.revealed_answers, ["second"]);
+    }
+
+    #[test]
+    fn quick_round_transition_ends_on_the_new_round() {
+        let round_finish_time = timestamp(30);
+        let mut client = WordgamesClient::default();
+        for frame in [
+            format!(
+                r#"{{"type":"FinishedRoundInfo","content":{{"word_answer":"second","to_next_round_time":"{}"}}}}"#,
+                timestamp(0)
+            ),
+            format!(
+                r#"{{"type":"OngoingRoundInfo","content":{{"word_to_guess":"driht","round_finish_time":"{round_finish_time}"}}}}"#
+            ),
+        ] {
+            client.ws_result_received(Ok(frame));
+        }
+
+        assert_eq!(client.word_box, "driht");
+        assert_eq!(client.status_text, client.settings.ongoing_round_guide);
+        assert!(client.revealed_answers.is_empty());
+        assert_eq!(
+            client.timer_finish_time,
+            Some(OffsetDateTime::parse(&round_finish_time, &Iso8601::DEFAULT).unwrap())
+        );
+    }
+
+    fn address_of(url: &str) -> Result<(String, u16), String> {
+        let uri: Uri = url.parse().unwrap();
+        server_address(&uri).map(|(host, port)| (host.to_owned(), port))
+    }
+
+    #[test]
+    fn ipv6_literal_loses_its_brackets() {
+        assert_eq!(
+            address_of("ws://[::1]:3000/ws/anagram/1"),
+            Ok(("::1".to_owned(), 3000))
+        );
+    }
+
+    #[test]
+    fn port_defaults_to_the_scheme_port() {
+        assert_eq!(
+            address_of("ws://localhost/ws"),
+            Ok(("localhost".to_owned(), 80))
+        );
+        assert_eq!(
+            address_of("wss://example.com/ws"),
+            Ok(("example.com".to_owned(), 443))
+        );
+    }
+
+    #[test]
+    fn non_websocket_scheme_is_rejected() {
+        assert!(address_of("http://localhost:3000/ws").is_err());
+    }
+
+    /// Accepts websocket connections on a loopback port, keeping each open until the client closes it
+    fn loopback_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                thread::spawn(move || {
+                    if let Ok(mut socket) = tungstenite::accept(stream) {
+                        while socket.read_message().is_ok() {}
+                    }
+                });
+            }
+        });
+        format!("ws://{addr}")
+    }
+
+    #[test]
+    fn connecting_again_stops_the_previous_reader_thread() {
+        let url = loopback_server();
+        let (sender, old_thread_results) = connect(&url, Context::default()).unwrap();
+        // keep the old receiver here to see when the thread holding its sender exits
+        let mut client = WordgamesClient {
+            websocket: Some((sender, mpsc::channel().1)),
+            ..Default::default()
+        };
+
+        client.connect_to(url, &Context::default()).unwrap();
+
+        assert!(matches!(
+            old_thread_results.recv_timeout(Duration::from_secs(5)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        ));
+        assert!(client.websocket.is_some());
+    }
+
+    #[test]
+    fn reconnect_connects_off_the_ui_thread() {
+        let mut client = WordgamesClient {
+            primary_url: loopback_server(),
+            ..Default::default()
+        };
+        client.reconnect(&Context::default());
+        assert!(client.websocket.is_none());
+
+        let (url, receiver) = client.pending_reconnect.take().unwrap();
+        let result = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        client.reconnect_finished(url, result);
+        assert!(client.websocket.is_some());
+    }
+
+    #[test]
+    fn redirect_is_followed() {
+        let target = loopback_server();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // the handshake request fits in one read
+            let _ = stream.read(&mut [0; 1024]);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 302 Found\r\nLocation: {target}\r\nContent-Length: 0\r\n\r\n"
+            );
+        });
+
+        assert!(connect(&url, Context::default()).is_ok());
     }
 }
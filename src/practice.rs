@@ -0,0 +1,123 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use time::{format_description::well_known::Iso8601, OffsetDateTime};
+
+use crate::{ChatContent, ServerMessage, WordAnswer};
+
+const WORDS: &str = include_str!("../assets/practice_words.txt");
+const ROUND_DURATION: Duration = Duration::from_secs(30);
+const BREAK_DURATION: Duration = Duration::from_secs(5);
+
+fn format_time(time: OffsetDateTime) -> String {
+    time.format(&Iso8601::DEFAULT)
+        .expect("UTC timestamps are always representable in ISO 8601")
+}
+
+/// Offline stand-in for a server, producing the same messages a server would send
+pub struct PracticeSession {
+    answer: String,
+    phase_finish_time: OffsetDateTime,
+    round_ongoing: bool,
+    rng_state: u64,
+}
+
+impl PracticeSession {
+    pub fn start() -> (Self, ServerMessage) {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+
+        let mut session = Self {
+            answer: String::new(),
+            phase_finish_time: OffsetDateTime::now_utc(),
+            round_ongoing: false,
+            // xorshift needs a non-zero state
+            rng_state: seed | 1,
+        };
+        let first_round = session.start_round();
+
+        (session, first_round)
+    }
+
+    /// Advances to the next phase once the current one's time is up
+    pub fn tick(&mut self) -> Option<ServerMessage> {
+        if OffsetDateTime::now_utc() < self.phase_finish_time {
+            return None;
+        }
+
+        Some(if self.round_ongoing {
+            self.finish_round()
+        } else {
+            self.start_round()
+        })
+    }
+
+    pub fn guess(&mut self, guess: &str) -> Vec<ServerMessage> {
+        let mut messages = vec![ServerMessage::ChatMessage(ChatContent::Plain(format!(
+            "You: {guess}"
+        )))];
+
+        if !self.round_ongoing {
+            return messages;
+        }
+        if guess.trim().to_lowercase() == self.answer {
+            messages.push(ServerMessage::ChatMessage(ChatContent::Plain(
+                "You guessed it!".to_owned(),
+            )));
+            messages.push(self.finish_round());
+        } else {
+            messages.push(ServerMessage::ChatMessage(ChatContent::Plain(format!(
+                "{} is not the answer",
+                guess.trim()
+            ))));
+        }
+
+        messages
+    }
+
+    fn start_round(&mut self) -> ServerMessage {
+        let words: Vec<&str> = WORDS.lines().filter(|word| !word.is_empty()).collect();
+        self.answer = words[self.next_random(words.len())].to_owned();
+        self.round_ongoing = true;
+        self.phase_finish_time = OffsetDateTime::now_utc() + ROUND_DURATION;
+
+        ServerMessage::OngoingRoundInfo {
+            word_to_guess: self.scramble(),
+            round_finish_time: format_time(self.phase_finish_time),
+        }
+    }
+
+    fn finish_round(&mut self) -> ServerMessage {
+        self.round_ongoing = false;
+        self.phase_finish_time = OffsetDateTime::now_utc() + BREAK_DURATION;
+
+        ServerMessage::FinishedRoundInfo {
+            word_answer: WordAnswer::Single(self.answer.clone()),
+            to_next_round_time: format_time(self.phase_finish_time),
+        }
+    }
+
+    fn scramble(&mut self) -> String {
+        let mut letters: Vec<char> = self.answer.chars().collect();
+
+        // a few reshuffles make it very unlikely the scramble gives the answer away
+        for _ in 0..4 {
+            for idx in (1..letters.len()).rev() {
+                letters.swap(idx, self.next_random(idx + 1));
+            }
+            if letters.iter().collect::<String>() != self.answer {
+                break;
+            }
+        }
+
+        letters.into_iter().collect()
+    }
+
+    /// Random number in `0..bound` from a xorshift generator, good enough for picking words
+    fn next_random(&mut self, bound: usize) -> usize {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        (self.rng_state % bound as u64) as usize
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    io::{self, Read, Write},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use serde_json::{json, Value};
+
+const HANDSHAKE_OPCODE: u32 = 0;
+const FRAME_OPCODE: u32 = 1;
+
+#[derive(Clone, PartialEq)]
+pub struct Activity {
+    pub details: String,
+    pub state: String,
+    /// Unix timestamp Discord counts down to
+    pub end_time: Option<i64>,
+}
+
+trait IpcStream: Read + Write {}
+impl<T: Read + Write> IpcStream for T {}
+
+#[cfg(unix)]
+fn open_ipc() -> io::Result<Box<dyn IpcStream>> {
+    use std::os::unix::net::UnixStream;
+
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .unwrap_or_else(|| "/tmp".to_owned());
+    (0..10)
+        .find_map(|idx| UnixStream::connect(format!("{dir}/discord-ipc-{idx}")).ok())
+        .map(|stream| Box::new(stream) as Box<dyn IpcStream>)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Discord isn't running"))
+}
+
+#[cfg(windows)]
+fn open_ipc() -> io::Result<Box<dyn IpcStream>> {
+    use std::fs::OpenOptions;
+
+    (0..10)
+        .find_map(|idx| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(format!(r"\\?\pipe\discord-ipc-{idx}"))
+                .ok()
+        })
+        .map(|pipe| Box::new(pipe) as Box<dyn IpcStream>)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Discord isn't running"))
+}
+
+/// Frames are a little-endian opcode and length followed by a JSON payload
+fn write_frame(stream: &mut dyn IpcStream, opcode: u32, payload: &Value) -> io::Result<()> {
+    let payload = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+    stream.write_all(&frame)
+}
+
+/// Discord answers every frame, the answer is read so its buffer never fills up
+fn skip_frame(stream: &mut dyn IpcStream) -> io::Result<()> {
+    let mut header = [0; 8];
+    stream.read_exact(&mut header)?;
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    io::copy(&mut stream.take(u64::from(length)), &mut io::sink())?;
+    Ok(())
+}
+
+fn connect(client_id: &str) -> io::Result<Box<dyn IpcStream>> {
+    let mut stream = open_ipc()?;
+    write_frame(
+        stream.as_mut(),
+        HANDSHAKE_OPCODE,
+        &json!({ "v": 1, "client_id": client_id }),
+    )?;
+    skip_frame(stream.as_mut())?;
+    Ok(stream)
+}
+
+fn set_activity(
+    stream: &mut dyn IpcStream,
+    activity: Option<&Activity>,
+    nonce: u64,
+) -> io::Result<()> {
+    let activity = activity.map(|activity| {
+        let mut value = json!({ "details": activity.details, "state": activity.state });
+        if let Some(end_time) = activity.end_time {
+            value["timestamps"] = json!({ "end": end_time });
+        }
+        value
+    });
+    write_frame(
+        stream,
+        FRAME_OPCODE,
+        &json!({
+            "cmd": "SET_ACTIVITY",
+            "args": { "pid": std::process::id(), "activity": activity },
+            "nonce": nonce.to_string(),
+        }),
+    )?;
+    skip_frame(stream)
+}
+
+/// Shows the game on the player's Discord profile, talking to the local Discord app
+/// on its own thread so a missing or slow Discord never holds up the UI
+pub struct DiscordPresence {
+    client_id: String,
+    sender: Sender<Option<Activity>>,
+    activity: Option<Activity>,
+}
+
+impl DiscordPresence {
+    pub fn start(client_id: String) -> Self {
+        let (sender, receiver) = mpsc::channel::<Option<Activity>>();
+
+        let thread_client_id = client_id.clone();
+        thread::spawn(move || {
+            let mut stream = None;
+            for (nonce, activity) in receiver.iter().enumerate() {
+                // Discord may have started after the client, so retry on every update
+                if stream.is_none() {
+                    stream = connect(&thread_client_id).ok();
+                }
+                if let Some(connection) = &mut stream {
+                    if set_activity(connection.as_mut(), activity.as_ref(), nonce as u64).is_err() {
+                        stream = None;
+                    }
+                }
+            }
+        });
+
+        Self {
+            client_id,
+            sender,
+            activity: None,
+        }
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Sends `activity` to Discord if it changed, `None` clears the presence
+    pub fn update(&mut self, activity: Option<Activity>) {
+        if activity != self.activity {
+            self.activity = activity.clone();
+            let _ = self.sender.send(activity);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use eframe::egui::{Checkbox, DragValue, Slider, Ui};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Bump this and add a step to `Settings::migrate` whenever a saved field changes meaning
+pub const SETTINGS_VERSION: u32 = 2;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WordBoxAlignment {
+    Left,
+    Center,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WordBoxCasing {
+    AsReceived,
+    Upper,
+    Lower,
+}
+
+impl WordBoxCasing {
+    /// Only meant for display, guesses are still compared with the word as received
+    pub fn apply(self, word: &str) -> String {
+        match self {
+            WordBoxCasing::AsReceived => word.to_owned(),
+            WordBoxCasing::Upper => word.to_uppercase(),
+            WordBoxCasing::Lower => word.to_lowercase(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ChatLayout {
+    Full,
+    /// Only the latest few messages, without a scroll area
+    Ticker,
+}
+
+pub enum SettingsAction {
+    ApplyTheme,
+    TestDisconnectAlert,
+}
+
+/// Saved as JSON, fields missing from older saves get their default value
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema of the saved settings, saves from before versioning count as 1
+    pub version: u32,
+    pub waiting_round_guide: String,
+    pub finished_round_guide: String,
+    pub ongoing_round_guide: String,
+    pub word_box_letter_spacing: f32,
+    pub word_box_alignment: WordBoxAlignment,
+    pub word_box_casing: WordBoxCasing,
+    pub reduce_motion: bool,
+    pub ready_message: String,
+    pub auto_send_ready: bool,
+    pub send_on_enter: bool,
+    pub clear_correct_guess: bool,
+    pub strip_control_sequences: bool,
+    pub animate_new_messages: bool,
+    pub chat_layout: ChatLayout,
+    pub ticker_length: usize,
+    /// `None` uses the theme's body text size
+    pub chat_text_size: Option<f32>,
+    pub dim_when_unfocused: bool,
+    #[cfg(feature = "discord")]
+    pub discord_presence: bool,
+    /// Application ID of the Discord app the presence is shown for
+    #[cfg(feature = "discord")]
+    pub discord_client_id: String,
+    /// Empty uses the bundled default theme
+    pub theme_path: String,
+    pub auto_reconnect: bool,
+    pub replay_ready: bool,
+    pub alert_on_disconnect: bool,
+    /// Off by default, servers without feature negotiation show the hello as a chat message
+    pub negotiate_features: bool,
+    /// `None` keeps retrying forever
+    pub reconnect_attempt_limit: Option<u32>,
+    /// `None` stays connected however long the player is away
+    pub idle_disconnect_minutes: Option<u32>,
+    /// One URL per line, tried in turn with the server URL when reconnecting
+    pub fallback_server_urls: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            waiting_round_guide: "Waiting Round Start!".to_owned(),
+            finished_round_guide: "Time's up! The answer is:".to_owned(),
+            ongoing_round_guide: "Please guess:".to_owned(),
+            word_box_letter_spacing: 4.0,
+            word_box_alignment: WordBoxAlignment::Left,
+            word_box_casing: WordBoxCasing::AsReceived,
+            reduce_motion: false,
+            ready_message: crate::ClientMessage::Ready.to_frame(),
+            auto_send_ready: false,
+            send_on_enter: true,
+            clear_correct_guess: false,
+            strip_control_sequences: true,
+            animate_new_messages: true,
+            chat_layout: ChatLayout::Full,
+            ticker_length: 3,
+            chat_text_size: None,
+            dim_when_unfocused: true,
+            #[cfg(feature = "discord")]
+            discord_presence: false,
+            #[cfg(feature = "discord")]
+            discord_client_id: String::new(),
+            theme_path: String::new(),
+            auto_reconnect: false,
+            replay_ready: true,
+            alert_on_disconnect: false,
+            negotiate_features: false,
+            reconnect_attempt_limit: None,
+            fallback_server_urls: String::new(),
+            idle_disconnect_minutes: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads saved settings, upgrading older schemas first.
+    /// Also returns a description of every migration step that was applied
+    pub fn load(json: &str) -> Result<(Self, Vec<String>), String> {
+        let mut saved: Map<String, Value> =
+            serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let mut migrations = Vec::new();
+
+        // version 0 was never written, a hand-edited one is treated like an unversioned save
+        let saved_version = saved
+            .get("version")
+            .and_then(Value::as_u64)
+            .map_or(1, |version| {
+                u32::try_from(version).unwrap_or(u32::MAX).max(1)
+            });
+        if saved_version > SETTINGS_VERSION {
+            migrations.push(format!(
+                "settings were saved by a newer version ({saved_version}), unknown ones are dropped"
+            ));
+        }
+        for version in saved_version..SETTINGS_VERSION {
+            migrations.push(Self::migrate(version, &mut saved));
+        }
+
+        let defaults = match serde_json::to_value(Self::default()) {
+            Ok(Value::Object(defaults)) => defaults,
+            _ => unreachable!("settings always serialize to a JSON object"),
+        };
+        let missing: Vec<&str> = defaults
+            .keys()
+            .filter(|key| !saved.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            migrations.push(format!("used defaults for {}", missing.join(", ")));
+        }
+
+        saved.insert("version".to_owned(), SETTINGS_VERSION.into());
+        let settings =
+            serde_json::from_value(Value::Object(saved)).map_err(|err| err.to_string())?;
+        Ok((settings, migrations))
+    }
+
+    /// Upgrades `saved` from `version` to the next version
+    fn migrate(version: u32, saved: &mut Map<String, Value>) -> String {
+        match version {
+            // unversioned saves only lack the version field itself
+            1 => {
+                saved.insert("version".to_owned(), 2.into());
+                "added a version to settings saved before versioning".to_owned()
+            }
+            _ => unreachable!("every version below SETTINGS_VERSION has a migration"),
+        }
+    }
+
+    pub fn fallback_urls(&self) -> impl Iterator<Item = &str> {
+        self.fallback_server_urls
+            .lines()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    pub fn show(&mut self, ui: &mut Ui) -> Option<SettingsAction> {
+        let mut action = None;
+
+        ui.heading("Word box guides");
+        ui.horizontal(|ui| {
+            ui.label("Waiting:");
+            ui.text_edit_singleline(&mut self.waiting_round_guide);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Finished round:");
+            ui.text_edit_singleline(&mut self.finished_round_guide);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Ongoing round:");
+            ui.text_edit_singleline(&mut self.ongoing_round_guide);
+        });
+        if ui.button("Reset guides").clicked() {
+            let defaults = Self::default();
+            self.waiting_round_guide = defaults.waiting_round_guide;
+            self.finished_round_guide = defaults.finished_round_guide;
+            self.ongoing_round_guide = defaults.ongoing_round_guide;
+        }
+
+        ui.heading("Word box");
+        ui.horizontal(|ui| {
+            ui.label("Letter spacing:");
+            ui.add(Slider::new(&mut self.word_box_letter_spacing, 0.0..=24.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Alignment:");
+            ui.radio_value(&mut self.word_box_alignment, WordBoxAlignment::Left, "Left");
+            ui.radio_value(
+                &mut self.word_box_alignment,
+                WordBoxAlignment::Center,
+                "Center",
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Letter case:");
+            ui.radio_value(
+                &mut self.word_box_casing,
+                WordBoxCasing::AsReceived,
+                "As received",
+            );
+            ui.radio_value(&mut self.word_box_casing, WordBoxCasing::Upper, "Upper");
+            ui.radio_value(&mut self.word_box_casing, WordBoxCasing::Lower, "Lower");
+        });
+        ui.checkbox(&mut self.reduce_motion, "Reduce motion");
+
+        ui.heading("Ready signal");
+        ui.horizontal(|ui| {
+            ui.label("Ready message:");
+            ui.text_edit_singleline(&mut self.ready_message);
+        });
+        ui.checkbox(&mut self.auto_send_ready, "Send ready message on connect");
+
+        ui.heading("Message input");
+        ui.checkbox(&mut self.send_on_enter, "Send with Enter")
+            .on_hover_text("When off, messages are only sent with the Send button");
+        ui.checkbox(
+            &mut self.clear_correct_guess,
+            "Clear the message field when it holds the revealed answer",
+        );
+
+        ui.heading("Chat");
+        ui.checkbox(
+            &mut self.strip_control_sequences,
+            "Remove terminal color codes from messages",
+        )
+        .on_hover_text("Strips ANSI escape sequences sent by servers bridged from terminals");
+        ui.add_enabled(
+            !self.reduce_motion,
+            Checkbox::new(&mut self.animate_new_messages, "Fade in new messages"),
+        )
+        .on_disabled_hover_text("Off while Reduce motion is on");
+        ui.horizontal(|ui| {
+            ui.label("Layout:");
+            ui.radio_value(&mut self.chat_layout, ChatLayout::Full, "Full");
+            ui.radio_value(&mut self.chat_layout, ChatLayout::Ticker, "Ticker");
+        });
+        ui.horizontal(|ui| {
+            let mut custom = self.chat_text_size.is_some();
+            if ui.checkbox(&mut custom, "Custom text size").changed() {
+                self.chat_text_size = custom.then_some(14.0);
+            }
+            if let Some(size) = &mut self.chat_text_size {
+                ui.add(Slider::new(size, 8.0..=32.0));
+            }
+        });
+        ui.add_enabled_ui(self.chat_layout == ChatLayout::Ticker, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Ticker messages:");
+                ui.add(Slider::new(&mut self.ticker_length, 1..=3));
+            });
+        });
+
+        ui.heading("Connection");
+        ui.checkbox(
+            &mut self.auto_reconnect,
+            "Reconnect when the connection drops",
+        );
+        ui.add_enabled_ui(self.auto_reconnect, |ui| {
+            ui.horizontal(|ui| {
+                let mut limited = self.reconnect_attempt_limit.is_some();
+                if ui.checkbox(&mut limited, "Give up after").changed() {
+                    self.reconnect_attempt_limit = limited.then_some(5);
+                }
+                if let Some(limit) = &mut self.reconnect_attempt_limit {
+                    ui.add(DragValue::new(limit).clamp_range(1..=100));
+                    ui.label("attempts");
+                }
+            });
+            ui.label("Fallback server URLs, one per line:");
+            ui.text_edit_multiline(&mut self.fallback_server_urls);
+        });
+        ui.checkbox(
+            &mut self.replay_ready,
+            "Send ready again after reconnecting if it was sent before",
+        );
+
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.alert_on_disconnect,
+                "Flash the window when the connection drops",
+            )
+            .on_hover_text("Not when disconnecting yourself");
+            if ui.button("Test").clicked() {
+                action = Some(SettingsAction::TestDisconnectAlert);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut enabled = self.idle_disconnect_minutes.is_some();
+            if ui.checkbox(&mut enabled, "Disconnect after").changed() {
+                self.idle_disconnect_minutes = enabled.then_some(10);
+            }
+            if let Some(minutes) = &mut self.idle_disconnect_minutes {
+                ui.add(DragValue::new(minutes).clamp_range(1..=240));
+                ui.label("minutes without activity");
+            }
+        })
+        .response
+        .on_hover_text("Frees your slot on the server when you walk away");
+        ui.checkbox(
+            &mut self.negotiate_features,
+            "Ask the server which features it supports",
+        )
+        .on_hover_text(
+            "Sends a hello message on connect. Servers that don't support it may show it in chat",
+        );
+
+        ui.heading("Window");
+        ui.checkbox(
+            &mut self.dim_when_unfocused,
+            "Dim the window when it isn't focused",
+        );
+
+        #[cfg(feature = "discord")]
+        {
+            ui.heading("Discord");
+            ui.checkbox(
+                &mut self.discord_presence,
+                "Show the current round on your Discord profile",
+            );
+            ui.add_enabled_ui(self.discord_presence, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Application ID:");
+                    ui.text_edit_singleline(&mut self.discord_client_id)
+                        .on_hover_text("From the Discord developer portal");
+                });
+            });
+        }
+
+        ui.heading("Theme");
+        ui.horizontal(|ui| {
+            ui.label("Theme file:");
+            ui.text_edit_singleline(&mut self.theme_path)
+                .on_hover_text("Path to a JSON theme file, leave empty for the default theme");
+        });
+        if ui.button("Apply theme").clicked() {
+            action = Some(SettingsAction::ApplyTheme);
+        }
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_settings_are_migrated() {
+        let (settings, migrations) =
+            Settings::load(r#"{"waiting_round_guide":"Hold on","reduce_motion":true}"#).unwrap();
+
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.waiting_round_guide, "Hold on");
+        assert!(settings.reduce_motion);
+        assert_eq!(
+            settings.ongoing_round_guide,
+            Settings::default().ongoing_round_guide
+        );
+        assert_eq!(
+            migrations[0],
+            "added a version to settings saved before versioning"
+        );
+        assert!(migrations[1].starts_with("used defaults for "));
+        assert!(migrations[1].contains("ongoing_round_guide"));
+    }
+
+    #[test]
+    fn version_zero_is_migrated_like_unversioned() {
+        let (settings, migrations) = Settings::load(r#"{"version":0}"#).unwrap();
+
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(
+            migrations[0],
+            "added a version to settings saved before versioning"
+        );
+    }
+}
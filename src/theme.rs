@@ -0,0 +1,199 @@
+use std::fs;
+
+use eframe::{
+    egui::{style::Margin, Stroke, Style, TextStyle},
+    epaint::{Color32, FontId, Rounding, Shadow, Vec2},
+};
+use serde::Deserialize;
+
+const DEFAULT_THEME: &str = include_str!("../themes/default.json");
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeStroke {
+    width: f32,
+    color: [u8; 3],
+}
+
+impl ThemeStroke {
+    /// Rounds the width to whole physical pixels so thin lines stay sharp at any scale
+    fn to_stroke(self, pixels_per_point: f32) -> Stroke {
+        let [r, g, b] = self.color;
+        let width = if self.width > 0.0 {
+            (self.width * pixels_per_point).round().max(1.0) / pixels_per_point
+        } else {
+            0.0
+        };
+        Stroke::new(width, Color32::from_rgb(r, g, b))
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FontSizes {
+    small: f32,
+    body: f32,
+    monospace: f32,
+    button: f32,
+    heading: f32,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetStrokes {
+    noninteractive: ThemeStroke,
+    inactive: ThemeStroke,
+    hovered: ThemeStroke,
+    active: ThemeStroke,
+    open: ThemeStroke,
+}
+
+/// Style values loaded from a JSON theme file, see `themes/default.json`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    item_spacing: [f32; 2],
+    button_padding: [f32; 2],
+    window_margin: f32,
+    menu_margin: f32,
+    font_sizes: FontSizes,
+    window_stroke: ThemeStroke,
+    window_rounding: f32,
+    widget_rounding: f32,
+    widget_expansion: f32,
+    widget_bg_strokes: WidgetStrokes,
+    widget_fg_strokes: WidgetStrokes,
+    selection_stroke: ThemeStroke,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_THEME).expect("bundled default theme is valid")
+    }
+}
+
+impl Theme {
+    /// Loads the theme at `path`, or the bundled default theme if `path` is empty
+    pub fn load(path: &str) -> Result<Self, String> {
+        if path.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        let theme: Self =
+            serde_json::from_str(&contents).map_err(|err| format!("{path}: {err}"))?;
+        theme.validate().map_err(|err| format!("{path}: {err}"))?;
+
+        Ok(theme)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let FontSizes {
+            small,
+            body,
+            monospace,
+            button,
+            heading,
+        } = self.font_sizes;
+        if [small, body, monospace, button, heading]
+            .iter()
+            .any(|size| !(4.0..=72.0).contains(size))
+        {
+            return Err("font sizes must be between 4 and 72".to_owned());
+        }
+
+        let lengths = [
+            self.item_spacing[0],
+            self.item_spacing[1],
+            self.button_padding[0],
+            self.button_padding[1],
+            self.window_margin,
+            self.menu_margin,
+            self.window_rounding,
+            self.widget_rounding,
+            self.widget_expansion,
+        ];
+        if lengths
+            .iter()
+            .any(|length| !length.is_finite() || *length < 0.0)
+        {
+            return Err("spacing, margin, rounding and expansion can't be negative".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the style for the current `pixels_per_point`, call again when the window's scale changes
+pub fn create_app_style(theme: &Theme, pixels_per_point: f32) -> Style {
+    let mut app_style = Style::default();
+
+    app_style.spacing.item_spacing = Vec2::from(theme.item_spacing);
+    app_style.spacing.button_padding = Vec2::from(theme.button_padding);
+    app_style.spacing.window_margin = Margin::same(theme.window_margin);
+    app_style.spacing.menu_margin = Margin::same(theme.menu_margin);
+
+    app_style.text_styles.insert(
+        TextStyle::Small,
+        FontId::proportional(theme.font_sizes.small),
+    );
+    app_style
+        .text_styles
+        .insert(TextStyle::Body, FontId::proportional(theme.font_sizes.body));
+    app_style.text_styles.insert(
+        TextStyle::Monospace,
+        FontId::monospace(theme.font_sizes.monospace),
+    );
+    app_style.text_styles.insert(
+        TextStyle::Button,
+        FontId::proportional(theme.font_sizes.button),
+    );
+    app_style.text_styles.insert(
+        TextStyle::Heading,
+        FontId::proportional(theme.font_sizes.heading),
+    );
+
+    app_style.visuals.window_stroke = theme.window_stroke.to_stroke(pixels_per_point);
+    app_style.visuals.window_rounding = Rounding::same(theme.window_rounding);
+    app_style.visuals.window_shadow = Shadow::small_dark();
+
+    let widgets = &mut app_style.visuals.widgets;
+    for (visuals, bg_stroke, fg_stroke) in [
+        (
+            &mut widgets.noninteractive,
+            theme.widget_bg_strokes.noninteractive,
+            theme.widget_fg_strokes.noninteractive,
+        ),
+        (
+            &mut widgets.inactive,
+            theme.widget_bg_strokes.inactive,
+            theme.widget_fg_strokes.inactive,
+        ),
+        (
+            &mut widgets.hovered,
+            theme.widget_bg_strokes.hovered,
+            theme.widget_fg_strokes.hovered,
+        ),
+        (
+            &mut widgets.active,
+            theme.widget_bg_strokes.active,
+            theme.widget_fg_strokes.active,
+        ),
+        (
+            &mut widgets.open,
+            theme.widget_bg_strokes.open,
+            theme.widget_fg_strokes.open,
+        ),
+    ] {
+        visuals.rounding = Rounding::same(theme.widget_rounding);
+        visuals.bg_stroke = bg_stroke.to_stroke(pixels_per_point);
+        visuals.fg_stroke = fg_stroke.to_stroke(pixels_per_point);
+    }
+
+    widgets.hovered.expansion = theme.widget_expansion;
+    widgets.active.expansion = theme.widget_expansion;
+
+    app_style.visuals.selection.stroke = theme.selection_stroke.to_stroke(pixels_per_point);
+
+    app_style
+}